@@ -19,46 +19,195 @@
 // SOFTWARE.
 
 use crate::basket::Bk;
-use crate::data::Data;
-use crate::emu::Emu;
+use crate::data;
+use crate::data::{Data, Op, Policy};
+use crate::emu::{Emu, Opt};
 use crate::loc::Loc;
+use crate::object::Object;
+use std::cmp::Ordering;
 
 pub type Atom = fn(&mut Emu, Bk) -> Option<Data>;
 
+/// How many arguments `𝛼0`, `𝛼1`, ... an atom reads, besides `ρ`:
+/// exactly this many, or all the object has, like `int-sum` does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arity {
+    Fixed(usize),
+    Variadic,
+}
+
+/// The overflow policy of arithmetic atoms: reduction modulo `M`,
+/// if there is `Opt::ModularArithmetic(M)`, or wrapping around.
+fn policy(emu: &Emu) -> Policy {
+    emu.opts
+        .iter()
+        .find_map(|opt| match opt {
+            Opt::ModularArithmetic(m) => Some(Policy::Modular(*m)),
+            _ => None,
+        })
+        .unwrap_or(Policy::Wrapping)
+}
+
+/// Apply the operation under the overflow policy of the emulator,
+/// letting it know if the result overflowed.
+fn arith(emu: &mut Emu, bk: Bk, op: Op, a: Data, b: Data) -> Data {
+    let (d, overflowed) = data::apply(op, a, b, policy(emu));
+    if overflowed {
+        emu.overflow(bk);
+    }
+    d
+}
+
 pub fn int_add(emu: &mut Emu, bk: Bk) -> Option<Data> {
-    Some(emu.read(bk, Loc::Rho)? + emu.read(bk, Loc::Attr(0))?)
+    let (a, b) = (emu.read(bk, Loc::Rho)?, emu.read(bk, Loc::Attr(0))?);
+    Some(arith(emu, bk, Op::Add, a, b))
 }
 
 pub fn int_times(emu: &mut Emu, bk: Bk) -> Option<Data> {
-    Some(emu.read(bk, Loc::Rho)? * emu.read(bk, Loc::Attr(0))?)
+    let (a, b) = (emu.read(bk, Loc::Rho)?, emu.read(bk, Loc::Attr(0))?);
+    Some(arith(emu, bk, Op::Mul, a, b))
 }
 
 pub fn int_neg(emu: &mut Emu, bk: Bk) -> Option<Data> {
-    Some(-emu.read(bk, Loc::Rho)?)
+    let a = emu.read(bk, Loc::Rho)?;
+    Some(arith(emu, bk, Op::Sub, 0, a))
+}
+
+pub fn int_inc(emu: &mut Emu, bk: Bk) -> Option<Data> {
+    let a = emu.read(bk, Loc::Rho)?;
+    Some(arith(emu, bk, Op::Add, a, 1))
+}
+
+pub fn int_dec(emu: &mut Emu, bk: Bk) -> Option<Data> {
+    let a = emu.read(bk, Loc::Rho)?;
+    Some(arith(emu, bk, Op::Sub, a, 1))
 }
 
 pub fn int_sub(emu: &mut Emu, bk: Bk) -> Option<Data> {
-    Some(emu.read(bk, Loc::Rho)? - emu.read(bk, Loc::Attr(0))?)
+    let (a, b) = (emu.read(bk, Loc::Rho)?, emu.read(bk, Loc::Attr(0))?);
+    Some(arith(emu, bk, Op::Sub, a, b))
 }
 
 pub fn int_div(emu: &mut Emu, bk: Bk) -> Option<Data> {
-    Some(emu.read(bk, Loc::Rho)? / emu.read(bk, Loc::Attr(0))?)
+    let (a, b) = (emu.read(bk, Loc::Rho)?, emu.read(bk, Loc::Attr(0))?);
+    Some(arith(emu, bk, Op::Div, a, b))
 }
 
 pub fn int_less(emu: &mut Emu, bk: Bk) -> Option<Data> {
-    Some((emu.read(bk, Loc::Rho)? < emu.read(bk, Loc::Attr(0))?) as Data)
+    Some(data::from_bool(
+        emu.read(bk, Loc::Rho)? < emu.read(bk, Loc::Attr(0))?,
+    ))
+}
+
+/// Compare `ρ` with `𝛼0`, returning `-1`, `0`, or `1`.
+pub fn int_cmp(emu: &mut Emu, bk: Bk) -> Option<Data> {
+    let rho = emu.read(bk, Loc::Rho)?;
+    Some(match rho.cmp(&emu.read(bk, Loc::Attr(0))?) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    })
+}
+
+/// Read all arguments `𝛼0`, `𝛼1`, ... of the basket, stopping at the
+/// first one that is absent. All of them are requested at once, but
+/// `None` is returned until every one is dataized.
+pub fn read_args(emu: &mut Emu, bk: Bk) -> Option<Vec<Data>> {
+    let mut args = vec![];
+    let mut i = 0;
+    while emu.basket(bk).kids.contains_key(&Loc::Attr(i)) {
+        args.push(emu.read(bk, Loc::Attr(i)));
+        i += 1;
+    }
+    args.into_iter().collect()
+}
+
+pub fn int_sum(emu: &mut Emu, bk: Bk) -> Option<Data> {
+    let rho = emu.read(bk, Loc::Rho);
+    let args = read_args(emu, bk)?;
+    let items: Vec<Data> = std::iter::once(rho?).chain(args).collect();
+    let (d, overflowed) = data::sum(&items, policy(emu));
+    if overflowed {
+        emu.overflow(bk);
+    }
+    Some(d)
 }
 
 pub fn bool_if(emu: &mut Emu, bk: Bk) -> Option<Data> {
-    let term = emu.read(bk, Loc::Rho)?;
-    emu.read(bk, Loc::Attr(if term == 1 { 0 } else { 1 }))
+    let term = emu.read_or_request(bk, Loc::Rho)?;
+    emu.read_or_request(bk, Loc::Attr(if data::is_truthy(term) { 0 } else { 1 }))
+}
+
+/// The atom that never finishes, standing for the atoms that
+/// are not implemented, see `Object::from_str_lenient`.
+pub fn unknown(_emu: &mut Emu, _bk: Bk) -> Option<Data> {
+    None
+}
+
+/// All built-in atoms, by the names they have in programs.
+pub fn builtins() -> &'static [(&'static str, Atom, Arity)] {
+    &[
+        ("int-times", int_times, Arity::Fixed(1)),
+        ("int-div", int_div, Arity::Fixed(1)),
+        ("int-sub", int_sub, Arity::Fixed(1)),
+        ("int-add", int_add, Arity::Fixed(1)),
+        ("int-sum", int_sum, Arity::Variadic),
+        ("int-neg", int_neg, Arity::Fixed(0)),
+        ("int-inc", int_inc, Arity::Fixed(0)),
+        ("int-dec", int_dec, Arity::Fixed(0)),
+        ("bool-if", bool_if, Arity::Fixed(2)),
+        ("int-less", int_less, Arity::Fixed(1)),
+        ("int-cmp", int_cmp, Arity::Fixed(1)),
+    ]
+}
+
+/// Find the atom by the name it has in programs, like `int-add`.
+pub fn by_name(name: &str) -> Option<Atom> {
+    builtins()
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, atom, _)| *atom)
+}
+
+/// The attributes the atom of the object reads: `ρ` and the first
+/// arguments `𝛼0`, `𝛼1`, etc., according to its `Arity`. Nothing is
+/// returned if the object is not a built-in atom.
+pub fn operands(obj: &Object) -> Option<Vec<Loc>> {
+    let (name, _) = obj.lambda.as_ref()?;
+    let (_, _, arity) = builtins().iter().find(|(n, _, _)| n == name)?;
+    let args = match arity {
+        Arity::Fixed(n) => *n,
+        Arity::Variadic => (0..=i8::MAX)
+            .take_while(|i| obj.attrs.contains_key(&Loc::Attr(*i)))
+            .count(),
+    };
+    Some(
+        std::iter::once(Loc::Rho)
+            .chain((0..args).map(|i| Loc::Attr(i as i8)))
+            .collect(),
+    )
 }
 
 #[cfg(test)]
 use crate::assert_dataized_eq;
 
 #[cfg(test)]
-use crate::emu::Opt;
+use std::str::FromStr;
+
+#[test]
+pub fn lists_operands_of_atoms() {
+    let ops = |text: &str| operands(&Object::from_str(text).unwrap());
+    assert_eq!(
+        Some(vec![Loc::Rho, Loc::Attr(0)]),
+        ops("⟦ λ ↦ int-add, ρ ↦ ν1 ⟧")
+    );
+    assert_eq!(
+        Some(vec![Loc::Rho, Loc::Attr(0), Loc::Attr(1)]),
+        ops("⟦ λ ↦ int-sum, ρ ↦ ν1, 𝛼0 ↦ ν1, 𝛼1 ↦ ν1 ⟧")
+    );
+    assert_eq!(Some(vec![Loc::Rho]), ops("⟦ λ ↦ int-sum, ρ ↦ ν1 ⟧"));
+    assert_eq!(None, ops("⟦ 𝜑 ↦ ν1 ⟧"));
+}
 
 #[test]
 pub fn bool_if_works() {
@@ -82,6 +231,16 @@ pub fn bool_if_works() {
         ν4(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
     "
     );
+    assert_dataized_eq!(
+        42,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν1, 𝛼0 ↦ ν3, 𝛼1 ↦ ν4 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν4(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+    "
+    );
 }
 
 #[test]
@@ -166,3 +325,195 @@ pub fn int_less_works() {
     "
     );
 }
+
+#[test]
+pub fn int_sum_works() {
+    assert_dataized_eq!(
+        10,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-sum, ρ ↦ ν1, 𝛼0 ↦ ν3, 𝛼1 ↦ ν4, 𝛼2 ↦ ν5 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν4(𝜋) ↦ ⟦ Δ ↦ 0x0003 ⟧
+        ν5(𝜋) ↦ ⟦ Δ ↦ 0x0004 ⟧
+    "
+    );
+}
+
+#[test]
+pub fn int_inc_and_dec_work() {
+    assert_dataized_eq!(
+        43,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-inc, ρ ↦ ν1 ⟧
+    "
+    );
+    assert_dataized_eq!(
+        41,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-dec, ρ ↦ ν1 ⟧
+    "
+    );
+    assert_dataized_eq!(
+        Data::MIN,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x7FFF ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-inc, ρ ↦ ν1 ⟧
+    "
+    );
+}
+
+#[test]
+pub fn int_cmp_works() {
+    assert_dataized_eq!(
+        -1,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-cmp, ρ ↦ ν1, 𝛼0 ↦ ν3 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+    "
+    );
+    assert_dataized_eq!(
+        0,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-cmp, ρ ↦ ν1, 𝛼0 ↦ ν3 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+    "
+    );
+    assert_dataized_eq!(
+        1,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-cmp, ρ ↦ ν1, 𝛼0 ↦ ν3 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+    "
+    );
+}
+
+#[test]
+pub fn int_cmp_works_with_large_numbers() {
+    assert_dataized_eq!(
+        1,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x7FFF ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-cmp, ρ ↦ ν1, 𝛼0 ↦ ν3 ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ ν1 ⟧
+    "
+    );
+    assert_dataized_eq!(
+        -1,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x7FFF ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-cmp, ρ ↦ ν3, 𝛼0 ↦ ν1 ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ ν1 ⟧
+    "
+    );
+}
+
+#[test]
+pub fn reduces_modulo_given_base() {
+    let mut emu: Emu = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x7FFF ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν1, 𝛼0 ↦ ν1 ⟧
+    "
+    .parse()
+    .unwrap();
+    emu.opt(Opt::ModularArithmetic(100));
+    assert_eq!(34, emu.dataize().0);
+    let mut emu: Emu = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-times, ρ ↦ ν1, 𝛼0 ↦ ν1 ⟧
+    "
+    .parse()
+    .unwrap();
+    emu.opt(Opt::ModularArithmetic(10));
+    assert_eq!(9, emu.dataize().0);
+}
+
+#[test]
+pub fn reduces_negative_result_into_positive() {
+    let mut emu: Emu = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ ν1 ⟧
+    "
+    .parse()
+    .unwrap();
+    emu.opt(Opt::ModularArithmetic(10));
+    assert_eq!(3, emu.dataize().0);
+}
+
+#[test]
+pub fn rejects_non_positive_modulus() {
+    let mut emu = Emu::empty();
+    assert_eq!(
+        "The modulus must be positive, while 0 given",
+        emu.set_modulus(0).err().unwrap()
+    );
+    assert!(emu.set_modulus(-7).is_err());
+    assert!(emu.set_modulus(7).is_ok());
+}
+
+#[test]
+pub fn keeps_only_the_latest_modulus() {
+    let mut emu: Emu = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-times, ρ ↦ ν1, 𝛼0 ↦ ν1 ⟧
+    "
+    .parse()
+    .unwrap();
+    emu.opt(Opt::ModularArithmetic(100));
+    emu.set_modulus(10).unwrap();
+    assert_eq!(1, emu.opts.len());
+    assert_eq!(9, emu.dataize().0);
+}
+
+#[test]
+pub fn lists_builtins() {
+    let names: Vec<&str> = builtins().iter().map(|(n, _, _)| *n).collect();
+    assert!(names.contains(&"int-add"));
+    assert!(names.contains(&"bool-if"));
+    for name in names.iter() {
+        assert!(by_name(name).is_some());
+        let obj = Object::atomic(name.to_string(), unknown);
+        assert!(operands(&obj).is_some(), "{} has no operands", name);
+    }
+    assert!(by_name("int-mod").is_none());
+}
+
+#[test]
+pub fn counts_overflows() {
+    let mut emu: Emu = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x7FFF ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-inc, ρ ↦ ν1 ⟧
+    "
+    .parse()
+    .unwrap();
+    let (d, perf) = emu.dataize();
+    assert_eq!(Data::MIN, d);
+    assert_eq!(1, perf.overflows);
+    let mut emu: Emu = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2 ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x7FFE ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-inc, ρ ↦ ν1 ⟧
+    "
+    .parse()
+    .unwrap();
+    assert_eq!(0, emu.dataize().1.overflows);
+}
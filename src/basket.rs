@@ -18,6 +18,7 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use crate::data;
 use crate::data::Data;
 use crate::loc::Loc;
 use crate::object::Ob;
@@ -30,6 +31,7 @@ use std::str::FromStr;
 
 pub type Bk = isize;
 
+#[derive(Clone)]
 pub enum Kid {
     Empt,
     Rqtd,
@@ -38,6 +40,7 @@ pub enum Kid {
     Dtzd(Data),
 }
 
+#[derive(Clone)]
 pub struct Basket {
     pub ob: Ob,
     pub psi: Bk,
@@ -61,6 +64,8 @@ impl Basket {
         }
     }
 
+    /// The basket is empty when its `psi` is negative, that's why
+    /// negative `psi` can't be used by a real basket.
     pub fn is_empty(&self) -> bool {
         self.psi < 0
     }
@@ -103,56 +108,87 @@ impl FromStr for Basket {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let re = Regex::new("\\[(.*)]").unwrap();
         let mut bsk = Basket::empty();
-        let parts: Vec<&str> = re
+        let caps = re
             .captures(s)
-            .unwrap_or_else(|| panic!("Can't parse the basket: '{}'", s))
+            .ok_or(format!("Can't parse the basket: '{}'", s))?;
+        let parts: Vec<&str> = caps[1].trim().split(',').map(|t| t.trim()).collect();
+        let ob = parts[0]
+            .strip_prefix('ν')
+            .ok_or(format!("Can't find the ν part in '{}'", s))?;
+        bsk.ob = ob
+            .parse()
+            .map_err(|e| format!("Can't parse the ν part in '{}': {}", s, e))?;
+        let psi = parts
             .get(1)
-            .unwrap_or_else(|| panic!("Can't find the matcher inside '{}'", s))
-            .as_str()
-            .trim()
-            .split(',')
-            .map(|t| t.trim())
-            .collect();
-        let ob: String = parts.first().unwrap().chars().skip(1).collect();
-        bsk.ob = ob.parse().expect("Can't parse the v part");
-        let psi: String = parts.get(1).unwrap().chars().skip(3).collect();
-        bsk.psi = psi.parse().expect("Can't parse the psi part");
-        let pre = Regex::new("^(.*)(⇶0x|⇉β|→\\(ν|→∅|→\\?)(.*?)\\)?$").unwrap();
+            .and_then(|p| p.strip_prefix("ξ:β"))
+            .ok_or(format!("Can't find the ξ part in '{}'", s))?;
+        bsk.psi = psi
+            .parse()
+            .map_err(|e| format!("Can't parse the ξ part in '{}': {}", s, e))?;
+        if bsk.psi < 0 {
+            return Err(format!(
+                "The ξ:β{} is reserved for empty baskets, can't use it in '{}'",
+                bsk.psi, s
+            ));
+        }
+        let pre = Regex::new("^(.*?)(⇶0x|⇉β|→\\(ν|→∅|→\\?)(.*)$").unwrap();
         for p in parts.iter().skip(2) {
-            let caps = pre.captures(p).unwrap();
-            let kid = match caps.get(2).unwrap().as_str() {
+            let caps = pre
+                .captures(p)
+                .ok_or(format!("Can't parse the kid '{}' in '{}'", p, s))?;
+            let broken = |msg: &str| format!("{} in the kid '{}' of '{}'", msg, p, s);
+            let rest = &caps[3];
+            let kid = match &caps[2] {
+                "→∅" | "→?" if !rest.is_empty() => {
+                    return Err(broken(&format!(
+                        "Unexpected '{}' after '{}'",
+                        rest, &caps[2]
+                    )))
+                }
                 "→∅" => Kid::Empt,
+                "→?" => Kid::Rqtd,
                 "⇶0x" => {
-                    let data = caps.get(3).unwrap().as_str();
-                    Kid::Dtzd(
-                        Data::from_str_radix(data, 16)
-                            .unwrap_or_else(|_| panic!("Can't parse data '{}'", data)),
-                    )
+                    if rest.is_empty() {
+                        return Err(broken("Data kid has no hex digits"));
+                    }
+                    Kid::Dtzd(data::from_hex(rest).map_err(|e| broken(&e))?)
                 }
                 "⇉β" => {
-                    let (b, a) = caps
-                        .get(3)
-                        .unwrap()
-                        .as_str()
-                        .split('.')
-                        .collect_tuple()
-                        .unwrap();
-                    Kid::Wait(b.parse().unwrap(), Loc::from_str(a).unwrap())
+                    let (b, a) = rest
+                        .split_once('.')
+                        .ok_or_else(|| broken("Wait kid misses '.'"))?;
+                    Kid::Wait(
+                        b.parse()
+                            .map_err(|_| broken(&format!("Wait kid has broken basket '{}'", b)))?,
+                        Loc::from_str(a)
+                            .map_err(|_| broken(&format!("Wait kid has broken loc '{}'", a)))?,
+                    )
                 }
                 "→(ν" => {
-                    let part = caps.get(3).unwrap().as_str();
-                    let (o, p) = part
-                        .split(';')
-                        .collect_tuple()
-                        .unwrap_or_else(|| panic!("Can't parse the needed pair '{}'", part));
-                    let psi: String = p.chars().skip(1).collect();
-                    Kid::Need(o.parse().unwrap(), psi.parse().unwrap())
+                    let body = rest
+                        .strip_suffix(')')
+                        .ok_or_else(|| broken("Need kid misses ')'"))?;
+                    let (o, b) = body
+                        .split_once(';')
+                        .ok_or_else(|| broken("Need kid misses ';'"))?;
+                    let psi = b
+                        .strip_prefix('β')
+                        .ok_or_else(|| broken("Need kid misses 'β'"))?;
+                    Kid::Need(
+                        o.parse()
+                            .map_err(|_| broken(&format!("Need kid has broken object '{}'", o)))?,
+                        psi.parse().map_err(|_| {
+                            broken(&format!("Need kid has broken basket '{}'", psi))
+                        })?,
+                    )
                 }
-                "→?" => Kid::Rqtd,
-                _ => panic!("Oops"),
+                _ => return Err(broken("Unknown kid")),
             };
-            bsk.kids
-                .insert(Loc::from_str(caps.get(1).unwrap().as_str()).unwrap(), kid);
+            bsk.kids.insert(
+                Loc::from_str(&caps[1])
+                    .map_err(|_| broken(&format!("Broken loc '{}'", &caps[1])))?,
+                kid,
+            );
         }
         Ok(bsk)
     }
@@ -193,3 +229,63 @@ fn parses_text(#[case] txt: &str) {
     let basket = Basket::from_str(txt).unwrap();
     assert_eq!(txt, basket.to_string());
 }
+
+#[test]
+fn reports_overflowing_data() {
+    let err = Basket::from_str("[ν5, ξ:β7, Δ⇶0x1FFFF]").err().unwrap();
+    assert!(
+        err.contains("Hex value 0x1FFFF overflows Data width"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn rejects_reserved_psi() {
+    let err = Basket::from_str("[ν5, ξ:β-1]").err().unwrap();
+    assert!(
+        err.starts_with("The ξ:β-1 is reserved for empty baskets"),
+        "{}",
+        err
+    );
+}
+
+#[rstest]
+#[case("")]
+#[case("[]")]
+#[case("[ν]")]
+#[case("[ν5]")]
+#[case("[ν5, ξ:β]")]
+#[case("[ν5, ξ:βx]")]
+#[case("[ν5, ξ:β-1]")]
+#[case("[5, ξ:β7]")]
+#[case("[ν5, ξ:β7, Δ⇶0x]")]
+#[case("[ν5, ξ:β7, Δ⇶0xZZ]")]
+#[case("[ν5, ξ:β7, ρ⇉β4]")]
+#[case("[ν5, ξ:β7, ρ⇉βx.𝜑]")]
+#[case("[ν5, ξ:β7, ρ⇉β4.$]")]
+#[case("[ν5, ξ:β7, 𝛼3→(ν5)]")]
+#[case("[ν5, ξ:β7, 𝛼3→(ν5;5)]")]
+#[case("[ν5, ξ:β7, $→?]")]
+#[case("[ν5, ξ:β7, 𝛼1]")]
+fn rejects_broken_basket(#[case] txt: &str) {
+    assert!(Basket::from_str(txt).is_err());
+}
+
+#[rstest]
+#[case("[ν5, ξ:β7, Δ⇶0x]", "Data kid has no hex digits")]
+#[case("[ν5, ξ:β7, ρ⇉β4]", "Wait kid misses '.'")]
+#[case("[ν5, ξ:β7, ρ⇉βx.𝜑]", "Wait kid has broken basket 'x'")]
+#[case("[ν5, ξ:β7, ρ⇉β4.$]", "Wait kid has broken loc '$'")]
+#[case("[ν5, ξ:β7, 𝛼3→(ν5;β5]", "Need kid misses ')'")]
+#[case("[ν5, ξ:β7, 𝛼3→(ν5)]", "Need kid misses ';'")]
+#[case("[ν5, ξ:β7, 𝛼3→(ν5;5)]", "Need kid misses 'β'")]
+#[case("[ν5, ξ:β7, 𝛼3→(νx;β5)]", "Need kid has broken object 'x'")]
+#[case("[ν5, ξ:β7, 𝛼3→(ν5;β)]", "Need kid has broken basket ''")]
+#[case("[ν5, ξ:β7, 𝜑→∅x]", "Unexpected 'x' after '→∅'")]
+#[case("[ν5, ξ:β7, 𝜑→?)]", "Unexpected ')' after '→?'")]
+#[case("[ν5, ξ:β7, $→?]", "Broken loc '$'")]
+fn explains_half_formed_kid(#[case] txt: &str, #[case] msg: &str) {
+    let err = Basket::from_str(txt).err().unwrap();
+    assert!(err.starts_with(msg), "{}", err);
+}
@@ -0,0 +1,116 @@
+// Copyright (c) 2022 Yegor Bugayenko
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included
+// in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NON-INFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+extern crate phie;
+
+use phie::emu::{Emu, Opt, MAX_OBJECTS};
+use phie::perf::Perf;
+use std::env;
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// All configurations run in the same pool, large enough for the
+/// ones that don't delete baskets.
+const BASKETS: usize = 256;
+
+/// The options to compare, by the names printed in the report.
+const CONFIGS: [(&str, &[Opt]); 5] = [
+    ("default", &[]),
+    ("DontDelete", &[Opt::DontDelete]),
+    ("Memoize", &[Opt::MemoizeConstants]),
+    ("Prioritize", &[Opt::PrioritizeTouched]),
+    (
+        "Memo+Prio",
+        &[Opt::MemoizeConstants, Opt::PrioritizeTouched],
+    ),
+];
+
+/// Run the program the given number of times with these options
+/// and return the performance of the last run, together with
+/// the total time spent.
+fn measure(phi_code: &str, opts: &[Opt], runs: usize) -> (Perf, Duration) {
+    let mut perf = Perf::new();
+    let time = Instant::now();
+    for _ in 0..runs {
+        let mut emu = Emu::with_capacity(MAX_OBJECTS, BASKETS);
+        emu.load(phi_code).unwrap();
+        for opt in opts {
+            emu.opt(opt.clone());
+        }
+        emu.opt(Opt::StopWhenTooManyCycles);
+        emu.opt(Opt::StopWhenStuck);
+        perf = emu.dataize().1;
+    }
+    (perf, time.elapsed())
+}
+
+/// Measure the program with all configurations, or only with
+/// the one of the given name.
+pub fn bench(phi_code: &str, runs: usize, only: Option<&str>) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{:<12} {:>8} {:>6} {:>8} {:>12}",
+        "config", "cycles", "peak", "atoms", "elapsed"
+    )];
+    for (name, opts) in CONFIGS.iter().filter(|(n, _)| only.is_none_or(|o| o == *n)) {
+        let (perf, elapsed) = measure(phi_code, opts, runs);
+        lines.push(format!(
+            "{:<12} {:>8} {:>6} {:>8} {:>12?}",
+            name,
+            perf.cycles,
+            perf.peak,
+            perf.total_atoms(),
+            elapsed
+        ));
+    }
+    lines
+}
+
+pub fn main() {
+    env_logger::init();
+    let args: Vec<String> = env::args().collect();
+    assert!(args.len() >= 3, "Usage: bench <file> <runs> [config]");
+    let phi_code = fs::read_to_string(&args[1]).unwrap();
+    let runs = args[2].parse().unwrap();
+    for line in bench(&phi_code, runs, args.get(3).map(|a| a.as_str())) {
+        println!("{}", line);
+    }
+}
+
+#[test]
+fn measures_sum_file() {
+    let lines = bench(
+        &fs::read_to_string("tests/resources/written_sum_test").unwrap(),
+        1,
+        None,
+    );
+    assert_eq!(6, lines.len());
+    assert!(lines[1].starts_with("default"));
+}
+
+#[test]
+fn measures_only_one_config() {
+    let lines = bench(
+        &fs::read_to_string("tests/resources/written_fibonacci_test").unwrap(),
+        1,
+        Some("Memoize"),
+    );
+    assert_eq!(2, lines.len());
+    assert!(lines[1].starts_with("Memoize"));
+}
@@ -21,29 +21,242 @@
 extern crate phie;
 
 use phie::data::Data;
-use phie::emu::{Emu, Opt};
+use phie::emu::{Emu, Opt, Outcome, MAX_BASKETS, MAX_OBJECTS};
+use phie::perf::Perf;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::str::FromStr;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::Duration;
 
-fn emulate(phi_code: &str) -> Data {
-    let mut emu: Emu = Emu::from_str(phi_code).unwrap();
+/// Options that are followed by a value on the command line.
+const VALUED: [&str; 6] = [
+    "--max-objects",
+    "--max-baskets",
+    "--trace-auto",
+    "--max-cycles",
+    "--max-atoms",
+    "--dump",
+];
+
+/// Options that stand alone on the command line, without a value.
+const FLAGS: [&str; 5] = ["--check", "--lint", "--trace", "--graph", "--binary"];
+
+/// Dataize the program and, if `dump` is given, write the final
+/// state of the Emu into that file, even if dataization fails.
+fn emulate(
+    phi_code: &str,
+    max_objects: usize,
+    max_baskets: usize,
+    dump: Option<&str>,
+) -> (Data, Perf) {
+    let mut emu = Emu::with_capacity(max_objects, max_baskets);
+    emu.load(phi_code).unwrap_or_else(|e| panic!("{}", e));
     emu.opt(Opt::LogSnapshots);
     emu.opt(Opt::StopWhenTooManyCycles);
     emu.opt(Opt::StopWhenStuck);
-    emu.dataize().0
+    let ret = panic::catch_unwind(AssertUnwindSafe(|| emu.try_dataize()));
+    finish(&emu, dump);
+    ret.unwrap_or_else(|e| panic::resume_unwind(e))
+        .unwrap_or_else(|e| panic!("{}", e))
 }
 
-pub fn run_emulator(filename: &str) -> i16 {
+/// Trace the program, like `trace` does, and, if `dump` is given,
+/// write the final state of the Emu into that file, like `emulate` does.
+fn emulate_traced(
+    phi_code: &str,
+    max_objects: usize,
+    max_baskets: usize,
+    dump: Option<&str>,
+    out: &mut impl Write,
+    pause: impl FnMut(),
+) -> (Data, Perf) {
+    let mut emu = Emu::with_capacity(max_objects, max_baskets);
+    emu.load(phi_code).unwrap_or_else(|e| panic!("{}", e));
+    let ret = panic::catch_unwind(AssertUnwindSafe(|| trace(&mut emu, out, pause)));
+    finish(&emu, dump);
+    let (outcome, perf) = ret.unwrap_or_else(|e| panic::resume_unwind(e));
+    (
+        outcome.into_result().unwrap_or_else(|e| panic!("{}", e)),
+        perf,
+    )
+}
+
+/// Print the warnings of the Emu and, if `dump` is given,
+/// write its state into that file.
+fn finish(emu: &Emu, dump: Option<&str>) {
+    for warning in emu.warnings() {
+        eprintln!("{}", warning);
+    }
+    if let Some(path) = dump {
+        fs::write(path, emu.snapshot())
+            .unwrap_or_else(|e| panic!("Can't dump the state to {}: {}", path, e));
+    }
+}
+
+fn run_emulator_with(
+    filename: &str,
+    max_objects: usize,
+    max_baskets: usize,
+    dump: Option<&str>,
+) -> (i16, Perf) {
     let binding = fs::read_to_string(filename).unwrap();
     let phi_code: &str = binding.as_str();
-    emulate(&phi_code)
+    emulate(phi_code, max_objects, max_baskets, dump)
+}
+
+pub fn run_emulator(filename: &str) -> i16 {
+    run_emulator_with(filename, MAX_OBJECTS, MAX_BASKETS, None).0
+}
+
+/// Find problems in the program, without running it.
+fn lint(filename: &str, max_objects: usize) -> Vec<String> {
+    let mut emu = Emu::with_capacity(max_objects, MAX_BASKETS);
+    emu.load(&fs::read_to_string(filename).unwrap())
+        .unwrap_or_else(|e| panic!("{}", e));
+    emu.unreachable_objects()
+        .iter()
+        .map(|ob| format!("Object ν{} is unreachable from ν0", ob))
+        .chain(
+            emu.dangling_references()
+                .iter()
+                .map(|(ob, loc, to)| format!("Object ν{}.{} refers to absent ν{}", ob, loc, to)),
+        )
+        .chain(emu.lint_atoms().into_iter().map(|(_, msg)| msg))
+        .collect()
+}
+
+/// Print direct references between objects, without running the program.
+fn graph(filename: &str, max_objects: usize) -> Vec<String> {
+    let mut emu = Emu::with_capacity(max_objects, MAX_BASKETS);
+    emu.load(&fs::read_to_string(filename).unwrap())
+        .unwrap_or_else(|e| panic!("{}", e));
+    emu.object_graph()
+        .iter()
+        .map(|(ob, loc, to)| format!("ν{}.{} → ν{}", ob, loc, to))
+        .collect()
+}
+
+/// Dataize cycle by cycle, printing live baskets after each cycle
+/// and calling `pause` before the next one.
+fn trace(emu: &mut Emu, out: &mut impl Write, mut pause: impl FnMut()) -> (Outcome, Perf) {
+    let mut perf = Perf::new();
+    loop {
+        let outcome = emu.advance(&mut perf);
+        writeln!(out, "Cycle #{}:", perf.cycles).unwrap();
+        for (bk, bsk) in emu.live_baskets() {
+            writeln!(out, "\tβ{} {}", bk, bsk).unwrap();
+        }
+        if let Some(outcome) = outcome {
+            return (outcome, perf);
+        }
+        if perf.cycles >= emu.max_cycles() {
+            return (Outcome::TooManyCycles(perf.cycles), perf);
+        }
+        pause();
+    }
+}
+
+/// Split the arguments into positional ones and `--name value` options.
+fn parse_args(args: &[String]) -> (Vec<String>, HashMap<String, String>) {
+    let mut positional = vec![];
+    let mut options = HashMap::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg.starts_with("--") {
+            let value = if VALUED.contains(&arg.as_str()) {
+                iter.next()
+                    .unwrap_or_else(|| panic!("The option {} needs a value", arg))
+                    .clone()
+            } else if FLAGS.contains(&arg.as_str()) {
+                String::new()
+            } else {
+                panic!("Unknown option {}", arg)
+            };
+            options.insert(arg.clone(), value);
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+    (positional, options)
+}
+
+/// Read a numeric option, if it is present.
+fn numeric(options: &HashMap<String, String>, name: &str) -> Option<usize> {
+    options.get(name).map(|v| {
+        v.parse()
+            .unwrap_or_else(|_| panic!("The option {} expects a number, not '{}'", name, v))
+    })
 }
 
 pub fn execute_program(args: &[String]) -> i16 {
+    let (args, options) = parse_args(args);
     assert!(args.len() >= 2);
     let filename: &str = &args[1];
-    let result: i16 = run_emulator(filename);
+    if options.contains_key("--check") {
+        let errors = Emu::check_all(&fs::read_to_string(filename).unwrap());
+        for (line, error) in errors.iter() {
+            eprintln!("{}:{}: {}", filename, line, error);
+        }
+        assert!(
+            errors.is_empty(),
+            "There are {} errors in {}",
+            errors.len(),
+            filename
+        );
+    }
+    if options.contains_key("--lint") {
+        for warning in lint(
+            filename,
+            numeric(&options, "--max-objects").unwrap_or(MAX_OBJECTS),
+        ) {
+            eprintln!("{}", warning);
+        }
+    }
+    let max_objects = numeric(&options, "--max-objects").unwrap_or(MAX_OBJECTS);
+    let max_baskets = numeric(&options, "--max-baskets").unwrap_or(MAX_BASKETS);
+    let (result, perf) = if options.contains_key("--trace") || options.contains_key("--trace-auto")
+    {
+        let delay = numeric(&options, "--trace-auto");
+        emulate_traced(
+            &fs::read_to_string(filename).unwrap(),
+            max_objects,
+            max_baskets,
+            options.get("--dump").map(|p| p.as_str()),
+            &mut io::stdout(),
+            || match delay {
+                Some(ms) => thread::sleep(Duration::from_millis(ms as u64)),
+                None => {
+                    io::stdin().read_line(&mut String::new()).unwrap();
+                }
+            },
+        )
+    } else {
+        run_emulator_with(
+            filename,
+            max_objects,
+            max_baskets,
+            options.get("--dump").map(|p| p.as_str()),
+        )
+    };
+    if let Some(max) = numeric(&options, "--max-cycles") {
+        assert!(
+            perf.cycles <= max,
+            "It took {} cycles, while no more than {} are allowed",
+            perf.cycles,
+            max
+        );
+    }
+    if let Some(max) = numeric(&options, "--max-atoms") {
+        assert!(
+            perf.total_atoms() <= max,
+            "There were {} atom calls, while no more than {} are allowed",
+            perf.total_atoms(),
+            max
+        );
+    }
     if args.len() >= 3 {
         let correct = args[2].parse::<i16>().unwrap();
         assert_eq!(result, correct);
@@ -55,8 +268,22 @@ pub fn main() {
     env_logger::init();
     let args: Vec<String> = env::args().collect();
     assert!(args.len() >= 2);
+    let (positional, options) = parse_args(&args);
+    if options.contains_key("--graph") {
+        let max_objects = numeric(&options, "--max-objects").unwrap_or(MAX_OBJECTS);
+        for line in graph(&positional[1], max_objects) {
+            println!("{}", line);
+        }
+        return;
+    }
     let result = execute_program(&args);
-    println!("Executor result: {}", result);
+    if options.contains_key("--binary") {
+        io::stdout()
+            .write_all(&result.to_be_bytes())
+            .expect("Can't write the result");
+    } else {
+        println!("Executor result: {}", result);
+    }
 }
 
 #[test]
@@ -97,3 +324,137 @@ fn executes_fibonacci_file() {
 fn executes_sum_file() {
     assert_eq!(84, run_emulator("tests/resources/written_sum_test"));
 }
+
+#[test]
+fn parses_options_apart_from_positional_args() {
+    let args = vec![
+        "program_name".to_string(),
+        "--max-objects".to_string(),
+        "32".to_string(),
+        "file".to_string(),
+    ];
+    let (positional, options) = parse_args(&args);
+    assert_eq!(vec!["program_name", "file"], positional);
+    assert_eq!(Some(32), numeric(&options, "--max-objects"));
+}
+
+#[test]
+fn parses_flags_without_values() {
+    let args = vec![
+        "program_name".to_string(),
+        "--binary".to_string(),
+        "file".to_string(),
+    ];
+    let (positional, options) = parse_args(&args);
+    assert_eq!(vec!["program_name", "file"], positional);
+    assert!(options.contains_key("--binary"));
+}
+
+#[test]
+#[should_panic(expected = "Unknown option --binray")]
+fn refuses_unknown_option() {
+    parse_args(&["program_name".to_string(), "--binray".to_string()]);
+}
+
+#[test]
+fn lints_unreachable_objects() {
+    assert_eq!(
+        vec!["Object ν5 is unreachable from ν0"],
+        lint("tests/resources/written_sum_test", MAX_OBJECTS)
+    );
+    assert!(lint("tests/resources/written_test_example", MAX_OBJECTS).is_empty());
+}
+
+#[test]
+fn traces_cycle_by_cycle() {
+    let mut emu = Emu::empty();
+    emu.load(&fs::read_to_string("tests/resources/written_sum_test").unwrap())
+        .unwrap();
+    let mut out = vec![];
+    let mut pauses = 0;
+    assert_eq!(
+        Outcome::Value(84),
+        trace(&mut emu, &mut out, || pauses += 1).0
+    );
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(6, text.matches("Cycle #").count(), "{}", text);
+    assert_eq!(5, pauses);
+    assert!(text.contains("\tβ0 [ν0, ξ:β0, 𝜑⇶0x0054]"), "{}", text);
+}
+
+#[test]
+fn stops_tracing_on_exceeded_read_budget() {
+    let mut emu = Emu::empty();
+    emu.load(&fs::read_to_string("tests/resources/written_sum_test").unwrap())
+        .unwrap();
+    emu.set_max_reads(1);
+    let (outcome, perf) = trace(&mut emu, &mut vec![], || {});
+    assert!(matches!(outcome, Outcome::TooManyReads { max: 1, .. }));
+    assert!(perf.cycles < emu.max_cycles());
+}
+
+#[test]
+fn dumps_final_state_when_tracing() {
+    let path = env::temp_dir().join("phie-dumps-traced-state.phie");
+    let dump = path.to_str().unwrap();
+    let (result, _) = emulate_traced(
+        &fs::read_to_string("tests/resources/written_sum_test").unwrap(),
+        MAX_OBJECTS,
+        MAX_BASKETS,
+        Some(dump),
+        &mut vec![],
+        || {},
+    );
+    assert_eq!(84, result);
+    let text = fs::read_to_string(&path).unwrap();
+    assert!(text.contains("β0 [ν0, ξ:β0, 𝜑⇶0x0054]"), "{}", text);
+}
+
+#[test]
+fn dumps_final_state() {
+    let path = env::temp_dir().join("phie-dumps-final-state.phie");
+    let dump = path.to_str().unwrap();
+    assert_eq!(
+        84,
+        run_emulator_with(
+            "tests/resources/written_sum_test",
+            MAX_OBJECTS,
+            MAX_BASKETS,
+            Some(dump)
+        )
+        .0
+    );
+    let text = fs::read_to_string(&path).unwrap();
+    let emu = Emu::restore(&text).unwrap();
+    assert_eq!(text, emu.snapshot());
+    assert!(text.contains("β0 [ν0, ξ:β0, 𝜑⇶0x0054]"), "{}", text);
+}
+
+#[test]
+fn dumps_negative_data() {
+    let program = env::temp_dir().join("phie-dumps-negative-data.phie");
+    fs::write(
+        &program,
+        "ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧\n\
+        ν1(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ ν2(𝜋) ⟧\n\
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧\n\
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0xFFFF ⟧\n",
+    )
+    .unwrap();
+    let path = env::temp_dir().join("phie-dumps-negative-data.dump");
+    assert_eq!(
+        -7,
+        run_emulator_with(
+            program.to_str().unwrap(),
+            MAX_OBJECTS,
+            MAX_BASKETS,
+            path.to_str()
+        )
+        .0
+    );
+    let text = fs::read_to_string(&path).unwrap();
+    let emu = Emu::restore(&text).unwrap();
+    assert_eq!(text, emu.snapshot());
+    assert!(text.contains("β0 [ν0, ξ:β0, 𝜑⇶0xFFF9]"), "{}", text);
+    assert!(text.contains("Δ↦0xFFFF"), "{}", text);
+}
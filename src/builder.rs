@@ -0,0 +1,134 @@
+// Copyright (c) 2022 Yegor Bugayenko
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included
+// in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NON-INFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::atom;
+use crate::data::Data;
+use crate::emu::{Emu, MAX_BASKETS, MAX_OBJECTS, ROOT_OB};
+use crate::loc::Loc;
+use crate::locator::Locator;
+use crate::object::{Ob, Object};
+
+/// Builds a program object by object, taking care of their positions,
+/// while `ν0` is reserved for the root one:
+///
+/// ```
+/// use phie::builder::ProgramBuilder;
+/// let mut b = ProgramBuilder::new();
+/// let x = b.data(42);
+/// let sum = b.atom("int-add", x, &[x]).unwrap();
+/// assert_eq!(84, b.build(sum).unwrap().dataize().0);
+/// ```
+pub struct ProgramBuilder {
+    objects: Vec<Object>,
+}
+
+impl Default for ProgramBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgramBuilder {
+    pub fn new() -> ProgramBuilder {
+        ProgramBuilder {
+            objects: vec![Object::open()],
+        }
+    }
+
+    /// Add a data object, like `⟦ Δ ↦ 0x002A ⟧`.
+    pub fn data(&mut self, d: Data) -> Ob {
+        self.add(Object::dataic(d))
+    }
+
+    /// Add an atom with its `ρ` and arguments `𝛼0`, `𝛼1`, etc. It's an
+    /// error if there is no such atom or too many arguments.
+    pub fn atom(&mut self, name: &str, rho: Ob, args: &[Ob]) -> Result<Ob, String> {
+        let func = atom::by_name(name).ok_or(format!("Unknown atom '{}'", name))?;
+        let mut obj = Object::atomic(name.to_string(), func).with(Loc::Rho, link(rho), false);
+        for (i, arg) in args.iter().enumerate() {
+            let i = i8::try_from(i)
+                .map_err(|_| format!("Too many arguments for '{}': {}", name, args.len()))?;
+            obj = obj.with(Loc::Attr(i), link(*arg), false);
+        }
+        Ok(self.add(obj))
+    }
+
+    /// Add an object, which decorates the target one, like `⟦ 𝜑 ↦ ν3 ⟧`.
+    pub fn decorator(&mut self, target: Ob) -> Ob {
+        self.add(Object::open().with(Loc::Phi, link(target), false))
+    }
+
+    /// Make an `Emu` with all the objects, where the root
+    /// one decorates the given object, which must be there.
+    pub fn build(self, root: Ob) -> Result<Emu, String> {
+        if root == ROOT_OB || root >= self.objects.len() {
+            return Err(format!("There is no object ν{} to decorate", root));
+        }
+        let mut emu = Emu::with_capacity(self.objects.len().max(MAX_OBJECTS), MAX_BASKETS);
+        for (ob, obj) in self.objects.into_iter().enumerate().skip(1) {
+            emu.put(ob, obj);
+        }
+        emu.put(ROOT_OB, Object::open().with(Loc::Phi, link(root), false));
+        Ok(emu)
+    }
+
+    fn add(&mut self, obj: Object) -> Ob {
+        self.objects.push(obj);
+        self.objects.len() - 1
+    }
+}
+
+fn link(ob: Ob) -> Locator {
+    Locator::from_loc(Loc::Obj(ob))
+}
+
+#[test]
+fn puts_objects_in_order() {
+    let mut b = ProgramBuilder::new();
+    assert_eq!(1, b.data(7));
+    assert_eq!(Ok(2), b.atom("int-neg", 1, &[]));
+    assert_eq!(3, b.decorator(2));
+    let emu = b.build(3).unwrap();
+    assert_eq!("⟦λ↦int-neg, ρ↦ν1(𝜋)⟧", emu.object(2).to_string());
+    assert_eq!("⟦𝜑↦ν3(𝜋)⟧", emu.object(0).to_string());
+}
+
+#[test]
+fn refuses_unknown_atom() {
+    assert_eq!(
+        Err("Unknown atom 'int-pow'".to_string()),
+        ProgramBuilder::new().atom("int-pow", 1, &[])
+    );
+}
+
+#[test]
+fn refuses_too_many_arguments() {
+    assert_eq!(
+        Err("Too many arguments for 'int-sum': 200".to_string()),
+        ProgramBuilder::new().atom("int-sum", 1, &[1; 200])
+    );
+}
+
+#[test]
+fn refuses_missing_root() {
+    let mut b = ProgramBuilder::new();
+    b.data(7);
+    assert!(b.build(2).is_err());
+}
@@ -19,3 +19,237 @@
 // SOFTWARE.
 
 pub type Data = i16;
+
+/// Booleans are encoded as `Data`, the way `bool-if` and `int-less` do.
+pub const TRUE: Data = 1;
+pub const FALSE: Data = 0;
+
+/// Any value, except zero, is true.
+pub fn is_truthy(d: Data) -> bool {
+    d != FALSE
+}
+
+pub fn from_bool(b: bool) -> Data {
+    if b {
+        TRUE
+    } else {
+        FALSE
+    }
+}
+
+/// Parse hex digits (without the `0x` prefix) into `Data`, telling
+/// a malformed string apart from a value that doesn't fit. The digits
+/// are the bits of `Data` in two's complement, so `FFFF` is `-1`, and
+/// only more digits than `Data` has room for are an overflow.
+pub fn from_hex(hex: &str) -> Result<Data, String> {
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Can't parse hex '{}'", hex));
+    }
+    if hex.len() > (Data::BITS / 4) as usize {
+        return Err(format!(
+            "Hex value 0x{} overflows Data width of {} bits, use --wide-data",
+            hex,
+            Data::BITS
+        ));
+    }
+    Ok(u16::from_str_radix(hex, 16).map_err(|_| format!("Can't parse hex '{}'", hex))? as Data)
+}
+
+/// Parse space-separated hex bytes, like `FF FF FF FF FF FF FF FF`, the
+/// way XMIR keeps integers: big-endian, where the bytes that don't
+/// fit into `Data` may only extend its sign.
+pub fn from_xmir_bytes(s: &str) -> Result<Data, String> {
+    let bytes = s
+        .split_whitespace()
+        .map(|b| {
+            u8::from_str_radix(b, 16).map_err(|_| format!("Can't parse byte '{}' in '{}'", b, s))
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+    let width = std::mem::size_of::<Data>();
+    if bytes.len() < width {
+        return Err(format!(
+            "There are {} bytes in '{}', while {} are expected",
+            bytes.len(),
+            s,
+            width
+        ));
+    }
+    let (high, low) = bytes.split_at(bytes.len() - width);
+    let d = Data::from_be_bytes(low.try_into().unwrap());
+    let sign = if d < 0 { 0xFF } else { 0x00 };
+    if high.iter().any(|b| *b != sign) {
+        return Err(format!("Bytes '{}' overflow Data width", s));
+    }
+    Ok(d)
+}
+
+/// Print `Data` as eight space-separated hex bytes, the way XMIR does.
+pub fn to_xmir_bytes(d: Data) -> String {
+    (d as i64)
+        .to_be_bytes()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// An integer wide enough to keep the exact result of any
+/// arithmetic on two `Data` values.
+type Exact = i64;
+
+/// Arithmetic operations on `Data`, which atoms apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// What to do with results of arithmetic, which don't fit into `Data`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Wrap around, exactly as two's complement hardware would do.
+    Wrapping,
+    /// Reduce the exact result modulo this positive number, into `0..M`.
+    Modular(Data),
+}
+
+/// Apply the operation to the operands, panicking on zero divisor,
+/// since there is no `Data` to represent the result. Along with the
+/// result, it tells whether it was wrapped around on overflow.
+pub fn apply(op: Op, a: Data, b: Data, policy: Policy) -> (Data, bool) {
+    let (a, b) = (a as Exact, b as Exact);
+    let exact = match op {
+        Op::Add => a + b,
+        Op::Sub => a - b,
+        Op::Mul => a * b,
+        Op::Div => {
+            assert_ne!(0, b, "Division of 0x{:04X} by zero", a as Data);
+            a / b
+        }
+    };
+    fit(exact, policy)
+}
+
+/// Sum up all the operands, like `apply` does with `Op::Add`, but
+/// wrapping around or reducing only the total.
+pub fn sum(items: &[Data], policy: Policy) -> (Data, bool) {
+    fit(items.iter().map(|d| *d as Exact).sum(), policy)
+}
+
+/// Make the exact result of arithmetic fit into `Data`.
+fn fit(exact: Exact, policy: Policy) -> (Data, bool) {
+    match policy {
+        Policy::Wrapping => {
+            let wrapped = exact as Data;
+            (wrapped, wrapped as Exact != exact)
+        }
+        Policy::Modular(m) => (exact.rem_euclid(m as Exact) as Data, false),
+    }
+}
+
+#[test]
+fn tells_truthy_from_falsy() {
+    assert!(is_truthy(TRUE));
+    assert!(is_truthy(-1));
+    assert!(is_truthy(42));
+    assert!(!is_truthy(FALSE));
+    assert_eq!(TRUE, from_bool(true));
+    assert_eq!(FALSE, from_bool(false));
+}
+
+#[test]
+fn parses_hex() {
+    assert_eq!(42, from_hex("002A").unwrap());
+}
+
+#[test]
+fn parses_negative_hex() {
+    assert_eq!(-1, from_hex("FFFF").unwrap());
+    assert_eq!(Data::MIN, from_hex("8000").unwrap());
+    assert_eq!(-7, from_hex(&format!("{:04X}", -7 as Data)).unwrap());
+}
+
+#[test]
+fn rejects_malformed_hex() {
+    assert_eq!("Can't parse hex 'ZZZZ'", from_hex("ZZZZ").unwrap_err());
+}
+
+#[test]
+fn rejects_overflowing_hex() {
+    assert_eq!(
+        "Hex value 0x1FFFF overflows Data width of 16 bits, use --wide-data",
+        from_hex("1FFFF").unwrap_err()
+    );
+}
+
+#[test]
+fn wraps_around_on_overflow() {
+    let w = Policy::Wrapping;
+    assert_eq!((Data::MIN, true), apply(Op::Add, Data::MAX, 1, w));
+    assert_eq!((Data::MAX, true), apply(Op::Sub, Data::MIN, 1, w));
+    assert_eq!((-2, true), apply(Op::Mul, Data::MAX, 2, w));
+    assert_eq!((Data::MIN, true), apply(Op::Div, Data::MIN, -1, w));
+    assert_eq!((Data::MIN, true), apply(Op::Sub, 0, Data::MIN, w));
+    assert_eq!((-1, false), apply(Op::Add, Data::MIN, Data::MAX, w));
+}
+
+#[test]
+fn calculates_without_overflow() {
+    let w = Policy::Wrapping;
+    assert_eq!((49, false), apply(Op::Add, 42, 7, w));
+    assert_eq!((35, false), apply(Op::Sub, 42, 7, w));
+    assert_eq!((294, false), apply(Op::Mul, 42, 7, w));
+    assert_eq!((6, false), apply(Op::Div, 42, 7, w));
+    assert_eq!((-42, false), apply(Op::Sub, 0, 42, w));
+}
+
+#[test]
+fn reduces_modulo_at_limits() {
+    let m = Policy::Modular(100);
+    assert_eq!((34, false), apply(Op::Add, Data::MAX, Data::MAX, m));
+    assert_eq!((64, false), apply(Op::Add, Data::MIN, Data::MIN, m));
+    assert_eq!((89, false), apply(Op::Mul, Data::MAX, Data::MAX, m));
+    assert_eq!((68, false), apply(Op::Div, Data::MIN, -1, m));
+    assert_eq!((3, false), apply(Op::Sub, 0, 7, Policy::Modular(10)));
+}
+
+#[test]
+fn sums_up_with_overflow_of_total_only() {
+    let w = Policy::Wrapping;
+    assert_eq!((Data::MAX, false), sum(&[Data::MAX, 1, -1], w));
+    assert_eq!((Data::MIN, true), sum(&[Data::MAX, 1], w));
+    assert_eq!((Data::MAX, true), sum(&[Data::MIN, -1], w));
+    assert_eq!(
+        (34, false),
+        sum(&[Data::MAX, Data::MAX], Policy::Modular(100))
+    );
+}
+
+#[test]
+#[should_panic(expected = "by zero")]
+fn refuses_to_divide_by_zero() {
+    apply(Op::Div, 42, 0, Policy::Wrapping);
+}
+
+#[test]
+fn round_trips_xmir_bytes() {
+    for d in [0, 1, -1, 42, Data::MIN, Data::MAX] {
+        assert_eq!(d, from_xmir_bytes(&to_xmir_bytes(d)).unwrap());
+    }
+    assert_eq!("FF FF FF FF FF FF FF FF", to_xmir_bytes(-1));
+    assert_eq!("00 00 00 00 00 00 00 2A", to_xmir_bytes(42));
+    assert_eq!(42, from_xmir_bytes("00 2A").unwrap());
+}
+
+#[test]
+fn rejects_too_wide_xmir_bytes() {
+    assert_eq!(
+        "Bytes '00 00 00 00 00 01 00 00' overflow Data width",
+        from_xmir_bytes("00 00 00 00 00 01 00 00").unwrap_err()
+    );
+    assert!(from_xmir_bytes("00 00 80 00").is_err());
+    assert!(from_xmir_bytes("2A").is_err());
+    assert!(from_xmir_bytes("00 ZZ").is_err());
+}
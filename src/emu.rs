@@ -18,27 +18,39 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod analysis;
 mod dataization;
+mod folding;
+mod results;
 mod tests;
 mod tests_transitions;
 mod transitions;
 
+use crate::atom;
 use crate::basket::{Basket, Bk, Kid};
 use crate::data::Data;
+pub use crate::emu::dataization::MAX_CYCLES;
 use crate::loc::Loc;
+use crate::locator::Locator;
 use crate::object::{Ob, Object};
-use arr_macro::arr;
-use log::trace;
+use crate::perf::{Perf, Transition};
+use crate::resolver::{Resolver, Standard};
+use itertools::Itertools;
+use log::{trace, warn};
 use regex::Regex;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str::FromStr;
 
 pub const ROOT_BK: Bk = 0;
 pub const ROOT_OB: Ob = 0;
 
-const MAX_OBJECTS: usize = 16;
-const MAX_BASKETS: usize = 128;
+pub const MAX_OBJECTS: usize = 16;
+pub const MAX_BASKETS: usize = 128;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Opt {
@@ -46,22 +58,228 @@ pub enum Opt {
     LogSnapshots,
     StopWhenTooManyCycles,
     StopWhenStuck,
+    VerifySharing,
+    EventLog,
+    MemoizeConstants,
+    TimeTransitions,
+    PrioritizeTouched,
+    LenientResolution,
+    /// Reduce the results of arithmetic atoms modulo this
+    /// positive number, into `0..M`.
+    ModularArithmetic(Data),
 }
 
+/// The way dataization ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Value(Data),
+    Stuck(String),
+    TooManyCycles(usize),
+    /// Atoms read kids more times than `Emu::set_max_reads` allows.
+    TooManyReads {
+        reads: usize,
+        max: usize,
+    },
+    /// A new basket would have more baskets in its chain of ξ than
+    /// `Emu::set_max_psi_depth` allows.
+    TooDeepXi {
+        depth: usize,
+        max: usize,
+    },
+}
+
+impl Outcome {
+    /// The value, if dataization finished, or the explanation
+    /// of why it didn't.
+    pub fn into_result(self) -> Result<Data, String> {
+        match self {
+            Outcome::Value(d) => Ok(d),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Value(d) => write!(f, "Dataized to 0x{:04X}", d),
+            Outcome::Stuck(msg) => write!(f, "{}", msg),
+            Outcome::TooManyCycles(cycles) => write!(
+                f,
+                "Too many cycles ({}), most probably endless recursion",
+                cycles
+            ),
+            Outcome::TooManyReads { reads, max } => write!(
+                f,
+                "The read budget exceeded: {} reads, while only {} allowed",
+                reads, max
+            ),
+            Outcome::TooDeepXi { depth, max } => write!(
+                f,
+                "The ξ chain is too deep: {} baskets, while only {} allowed",
+                depth, max
+            ),
+        }
+    }
+}
+
+/// A guess about whether dataization of the program finishes,
+/// made without running it, see `Emu::likely_terminates`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Terminates,
+    MayNotTerminate,
+    Unknown,
+}
+
+/// A problem, which didn't stop dataization, but may be worth
+/// reporting after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    Overflow {
+        bk: Bk,
+    },
+    UnresolvedAttr {
+        bk: Bk,
+        locator: Locator,
+    },
+    DeletedOperand {
+        bk: Bk,
+        loc: Loc,
+    },
+    UnknownLambda {
+        ob: Ob,
+        name: String,
+    },
+    MutableData {
+        ob: Ob,
+        bk: Bk,
+    },
+    SharedConstant {
+        ob: Ob,
+        bk: Bk,
+        psi: Bk,
+        expected: Bk,
+    },
+    ImpureConstant {
+        ob: Ob,
+        bk: Bk,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Overflow { bk } => write!(f, "Arithmetic overflow in β{}", bk),
+            Warning::UnresolvedAttr { bk, locator } => {
+                write!(f, "Can't resolve {} from β{}", locator, bk)
+            }
+            Warning::DeletedOperand { bk, loc } => {
+                write!(f, "The operand β{}.{} was deleted", bk, loc)
+            }
+            Warning::UnknownLambda { ob, name } => {
+                write!(f, "Unknown lambda '{}' in ν{}, it never finishes", name, ob)
+            }
+            Warning::MutableData { ob, bk } => {
+                write!(f, "Data object ν{} shared at β{} is not immutable", ob, bk)
+            }
+            Warning::SharedConstant {
+                ob,
+                bk,
+                psi,
+                expected,
+            } => write!(
+                f,
+                "Constant ν{} shared at β{} is in ξ:β{}, while ξ:β{} is expected",
+                ob, bk, psi, expected
+            ),
+            Warning::ImpureConstant { ob, bk } => write!(
+                f,
+                "Constant ν{} shared at β{} depends on 𝜋, it is not a real constant",
+                ob, bk
+            ),
+        }
+    }
+}
+
+/// The way dataization got stuck: the message, the number of cycles
+/// it took, and the kids that were still requested or waiting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StuckReport {
+    pub message: String,
+    pub cycles: usize,
+    pub blocked: Vec<(Bk, Loc)>,
+}
+
+/// A basket after dataization, together with the baskets
+/// of its attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultNode {
+    pub ob: Ob,
+    pub data: Option<Data>,
+    pub children: Vec<(Loc, ResultNode)>,
+}
+
+type DataizedListener = dyn FnMut(Bk, &Loc, Data);
+
 pub struct Emu {
-    pub objects: [Object; MAX_OBJECTS],
-    pub baskets: [Basket; MAX_BASKETS],
+    pub objects: Vec<Object>,
+    pub baskets: Vec<Basket>,
     pub opts: HashSet<Opt>,
+    refs: HashMap<Bk, usize>,
+    feeders: HashMap<Bk, usize>,
+    basket_limit: usize,
+    max_cycles: usize,
+    max_psi_depth: Option<usize>,
+    max_reads: Option<usize>,
+    transition_listener: Option<Box<dyn FnMut(Transition, Bk)>>,
+    dataized_listener: Option<Box<DataizedListener>>,
+    event_writer: Option<Box<dyn Write>>,
+    resolver: Rc<dyn Resolver>,
+    memo: HashMap<Ob, Data>,
+    touched: Vec<(Bk, Loc)>,
+    finished: Option<Perf>,
+    unresolved: Vec<(Bk, Loc, String)>,
+    overflows: usize,
+    reads: usize,
+    halted: Option<Outcome>,
+    warnings: Vec<Warning>,
+}
+
+/// The listeners and the event writer are not cloned,
+/// since they can't be shared between emulators.
+impl Clone for Emu {
+    fn clone(&self) -> Self {
+        Emu {
+            objects: self.objects.clone(),
+            baskets: self.baskets.clone(),
+            opts: self.opts.clone(),
+            refs: self.refs.clone(),
+            feeders: self.feeders.clone(),
+            basket_limit: self.basket_limit,
+            max_cycles: self.max_cycles,
+            max_psi_depth: self.max_psi_depth,
+            max_reads: self.max_reads,
+            transition_listener: None,
+            dataized_listener: None,
+            event_writer: None,
+            resolver: self.resolver.clone(),
+            memo: self.memo.clone(),
+            touched: self.touched.clone(),
+            finished: self.finished.clone(),
+            unresolved: self.unresolved.clone(),
+            overflows: self.overflows,
+            reads: self.reads,
+            halted: self.halted.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
 }
 
 impl fmt::Display for Emu {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut lines = vec![];
-        for i in self.objects.iter().enumerate() {
-            let (ob, obj): (usize, &Object) = i;
-            if obj.is_empty() {
-                continue;
-            }
+        for (ob, obj) in self.objects() {
             lines.push(format!(
                 "ν{} {}{}",
                 ob,
@@ -83,12 +301,7 @@ impl FromStr for Emu {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut emu = Emu::empty();
-        let re_line = Regex::new("ν(\\d+)\\(𝜋\\) ↦ (⟦.*⟧)").unwrap();
-        for line in s.trim().split('\n').map(|t| t.trim()) {
-            let caps = re_line.captures(line).unwrap();
-            let v: Ob = caps.get(1).unwrap().as_str().parse().unwrap();
-            emu.put(v, Object::from_str(caps.get(2).unwrap().as_str()).unwrap());
-        }
+        emu.load(s)?;
         Ok(emu)
     }
 }
@@ -112,21 +325,386 @@ impl Emu {
     /// Make an empty Emu, which you can later extend with
     /// additional objects.
     pub fn empty() -> Emu {
+        Emu::with_capacity(MAX_OBJECTS, MAX_BASKETS)
+    }
+
+    /// Make an empty Emu, which can hold up to the given number
+    /// of objects and baskets.
+    pub fn with_capacity(objects: usize, baskets: usize) -> Emu {
         let mut emu = Emu {
-            objects: arr![Object::open(); 16],
-            baskets: arr![Basket::empty(); 128],
+            objects: (0..objects).map(|_| Object::open()).collect(),
+            baskets: (0..baskets).map(|_| Basket::empty()).collect(),
             opts: HashSet::new(),
+            refs: HashMap::new(),
+            feeders: HashMap::new(),
+            basket_limit: baskets,
+            max_cycles: MAX_CYCLES,
+            max_psi_depth: None,
+            max_reads: None,
+            transition_listener: None,
+            dataized_listener: None,
+            event_writer: None,
+            resolver: Rc::new(Standard),
+            memo: HashMap::new(),
+            touched: vec![],
+            finished: None,
+            unresolved: vec![],
+            overflows: 0,
+            reads: 0,
+            halted: None,
+            warnings: vec![],
         };
+        emu.reset();
+        emu
+    }
+
+    /// Forget all baskets, keeping the objects, in order to
+    /// dataize the root object again from scratch.
+    pub fn reset(&mut self) {
+        for bsk in self.baskets.iter_mut() {
+            *bsk = Basket::empty();
+        }
+        self.refs.clear();
+        self.feeders.clear();
+        self.touched.clear();
+        self.finished = None;
+        self.unresolved.clear();
+        self.warnings = self.unknown_lambdas();
+        self.reads = 0;
+        self.halted = None;
         let mut basket = Basket::start(0, 0);
         basket.kids.insert(Loc::Phi, Kid::Rqtd);
-        emu.baskets[0] = basket;
+        self.baskets[ROOT_BK as usize] = basket;
+    }
+
+    /// Forget the values of constant objects, which are remembered
+    /// between runs with `Opt::MemoizeConstants`.
+    pub fn clear_memo(&mut self) {
+        self.memo.clear();
+    }
+
+    /// The value of the constant object, if it was dataized before.
+    pub fn memoized(&self, ob: Ob) -> Option<Data> {
+        if self.opts.contains(&Opt::MemoizeConstants) {
+            self.memo.get(&ob).copied()
+        } else {
+            None
+        }
+    }
+
+    /// The attributes that couldn't be found with
+    /// `Opt::LenientResolution`, together with the reasons.
+    pub fn unresolved(&self) -> &[(Bk, Loc, String)] {
+        &self.unresolved
+    }
+
+    /// Make an Emu with all these objects at their positions, checking
+    /// that none of them is out of capacity or given twice.
+    pub fn with_objects(objs: Vec<(Ob, Object)>) -> Result<Emu, String> {
+        let mut emu = Emu::empty();
+        for (ob, obj) in objs {
+            if ob >= emu.objects.len() {
+                return Err(format!(
+                    "Object ν{} is out of capacity, only {} objects are allowed",
+                    ob,
+                    emu.objects.len()
+                ));
+            }
+            if !emu.object(ob).is_empty() {
+                return Err(format!("Object ν{} is given twice", ob));
+            }
+            emu.put(ob, obj);
+        }
+        Ok(emu)
+    }
+
+    /// Make an Emu with objects, which many programs share, without
+    /// the root one, to run those programs later with `run_with`.
+    pub fn with_shared_library(lib: &str) -> Result<Emu, String> {
+        let mut emu = Emu::empty();
+        emu.load(lib)?;
+        Ok(emu)
+    }
+
+    /// Add the objects of the program, including the root one, to a
+    /// fresh copy of this Emu and dataize it. This Emu stays intact.
+    pub fn run_with(&self, main: &str) -> Result<Data, String> {
+        let mut emu = self.clone_program();
+        emu.load(main)?;
+        emu.evaluate().into_result()
+    }
+
+    /// Make an empty Emu with a small pool of baskets, which doubles
+    /// every time it runs out of empty baskets, up to the limit.
+    pub fn with_basket_growth(initial: usize, limit: usize) -> Emu {
+        let mut emu = Emu::with_capacity(MAX_OBJECTS, initial);
+        emu.basket_limit = limit;
+        emu
+    }
+
+    /// Make a fresh emulator with the same objects and options, but
+    /// without any baskets, except the root one, ready to dataize
+    /// the program from scratch.
+    pub fn clone_program(&self) -> Emu {
+        let mut emu = Emu::with_capacity(self.objects.len(), self.baskets.len());
+        emu.objects = self.objects.clone();
+        emu.opts = self.opts.clone();
+        emu.basket_limit = self.basket_limit;
+        emu.max_cycles = self.max_cycles;
+        emu.max_psi_depth = self.max_psi_depth;
+        emu.max_reads = self.max_reads;
+        emu.resolver = self.resolver.clone();
         emu
     }
 
+    /// Turn the option on. A later `Opt::ModularArithmetic` replaces
+    /// the earlier one, use `set_modulus` to have its modulus checked.
     pub fn opt(&mut self, opt: Opt) {
+        if let Opt::ModularArithmetic(m) = opt {
+            assert!(m > 0, "The modulus must be positive, while {} given", m);
+            self.opts
+                .retain(|o| !matches!(o, Opt::ModularArithmetic(_)));
+        }
         self.opts.insert(opt);
     }
 
+    /// Reduce the results of arithmetic atoms modulo this number,
+    /// like `Opt::ModularArithmetic` does, replacing the modulus
+    /// given before. It's an error if the modulus is not positive.
+    pub fn set_modulus(&mut self, m: Data) -> Result<(), String> {
+        if m <= 0 {
+            return Err(format!("The modulus must be positive, while {} given", m));
+        }
+        self.opt(Opt::ModularArithmetic(m));
+        Ok(())
+    }
+
+    /// Stop dataization after this many cycles, instead of
+    /// the default `MAX_CYCLES`.
+    pub fn set_max_cycles(&mut self, cycles: usize) {
+        self.max_cycles = cycles;
+    }
+
+    /// Call this function every time a transition happens
+    /// to one of the baskets.
+    pub fn on_transition(&mut self, f: impl FnMut(Transition, Bk) + 'static) {
+        self.transition_listener = Some(Box::new(f));
+    }
+
+    /// Call this function every time a kid of a basket gets
+    /// dataized, with the basket, the loc, and the data.
+    pub fn on_dataized(&mut self, f: impl FnMut(Bk, &Loc, Data) + 'static) {
+        self.dataized_listener = Some(Box::new(f));
+    }
+
+    /// Write one line per transition into this writer, when
+    /// `Opt::EventLog` is set: `cycle,transition,basket,object`.
+    pub fn set_event_writer(&mut self, w: impl Write + 'static) {
+        self.event_writer = Some(Box::new(w));
+    }
+
+    /// Find objects by locators with this resolver, instead of
+    /// the `Standard` one.
+    pub fn set_resolver(&mut self, resolver: impl Resolver + 'static) {
+        self.resolver = Rc::new(resolver);
+    }
+
+    /// Stop dataization with `Outcome::TooDeepXi` when a new basket is
+    /// about to have more than this many baskets in its chain of ξ,
+    /// instead of letting it grow.
+    pub fn set_max_psi_depth(&mut self, depth: usize) {
+        self.max_psi_depth = Some(depth);
+    }
+
+    /// Stop dataization with `Outcome::TooManyReads` when atoms read
+    /// kids more than this many times in total, no matter how many
+    /// cycles it takes.
+    pub fn set_max_reads(&mut self, reads: usize) {
+        self.max_reads = Some(reads);
+    }
+
+    /// How many objects this Emu can hold.
+    pub fn max_objects(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// How many baskets the pool may have, after growing to its limit.
+    pub fn max_baskets(&self) -> usize {
+        self.basket_limit
+    }
+
+    /// How many cycles dataization may take before it stops.
+    pub fn max_cycles(&self) -> usize {
+        self.max_cycles
+    }
+
+    /// Add objects from their text representation, one per line.
+    pub fn load(&mut self, s: &str) -> Result<&mut Emu, String> {
+        self.load_with(s, false)?;
+        Ok(self)
+    }
+
+    /// Load the program from the file, together with the files it
+    /// imports by lines like `import "lib.phie"`, which are found next
+    /// to the importing file. Objects keep their numbers, so the files
+    /// must not use the same ones, it's an error naming both files if
+    /// they do. Each file is loaded only once.
+    pub fn load_file(&mut self, path: &Path) -> Result<&mut Emu, String> {
+        self.load_imported(path, &mut vec![], &mut HashSet::new(), &mut HashMap::new())?;
+        Ok(self)
+    }
+
+    /// Load the file after all its imports, remembering the chain of
+    /// files being loaded, in order to catch cyclic imports, and the
+    /// files where objects came from, in order to catch clashes.
+    fn load_imported(
+        &mut self,
+        path: &Path,
+        chain: &mut Vec<PathBuf>,
+        done: &mut HashSet<PathBuf>,
+        origins: &mut HashMap<Ob, PathBuf>,
+    ) -> Result<(), String> {
+        let full = path
+            .canonicalize()
+            .map_err(|e| format!("Can't find {}: {}", path.display(), e))?;
+        if chain.contains(&full) {
+            return Err(format!("The import of {} is cyclic", path.display()));
+        }
+        if done.contains(&full) {
+            return Ok(());
+        }
+        let text = fs::read_to_string(&full)
+            .map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        chain.push(full.clone());
+        let mut lines = vec![];
+        for line in text.lines() {
+            match line.trim().strip_prefix("import ") {
+                Some(name) => {
+                    let lib = full.parent().unwrap().join(name.trim().trim_matches('"'));
+                    self.load_imported(&lib, chain, done, origins)?;
+                }
+                None => lines.push(line),
+            }
+        }
+        chain.pop();
+        done.insert(full);
+        let program = lines.join("\n");
+        if program.trim().is_empty() {
+            return Ok(());
+        }
+        for line in lines.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            let (ob, _) =
+                Emu::parse_line(line, false).map_err(|e| format!("{} in {}", e, path.display()))?;
+            if let Some(other) = origins.get(&ob) {
+                return Err(format!(
+                    "Object ν{} is defined in both {} and {}",
+                    ob,
+                    other.display(),
+                    path.display()
+                ));
+            }
+            origins.insert(ob, path.to_path_buf());
+        }
+        self.load_with(&program, false)
+            .map_err(|e| format!("{} in {}", e, path.display()))
+    }
+
+    /// Load the program, like `load` does, but let unknown lambdas
+    /// in, with `Warning::UnknownLambda` about each of them. Their
+    /// objects never get dataized.
+    pub fn load_lenient(&mut self, s: &str) -> Result<&mut Emu, String> {
+        self.load_with(s, true)?;
+        self.warnings
+            .retain(|w| !matches!(w, Warning::UnknownLambda { .. }));
+        let found = self.unknown_lambdas();
+        self.warnings.extend(found);
+        Ok(self)
+    }
+
+    /// Warnings about objects with lambdas, which are not atoms, the
+    /// way `load_lenient` lets them in.
+    fn unknown_lambdas(&self) -> Vec<Warning> {
+        self.objects()
+            .filter_map(|(ob, obj)| match &obj.lambda {
+                Some((name, _)) if atom::by_name(name).is_none() => Some(Warning::UnknownLambda {
+                    ob,
+                    name: name.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Load the program, skipping a leading byte-order mark, which
+    /// some editors put into UTF-8 files.
+    fn load_with(&mut self, s: &str, lenient: bool) -> Result<(), String> {
+        let s = s.strip_prefix('\u{feff}').unwrap_or(s);
+        if s.trim().is_empty() {
+            return Err("Program is empty".to_string());
+        }
+        for line in s.trim().split('\n').map(|t| t.trim()) {
+            let (v, obj) = Emu::parse_line(line, lenient)?;
+            if v >= self.objects.len() {
+                return Err(format!(
+                    "Object ν{} is out of capacity, only {} objects are allowed",
+                    v,
+                    self.objects.len()
+                ));
+            }
+            if !self.object(v).is_empty() {
+                return Err(format!("Object ν{} is given twice", v));
+            }
+            self.put(v, obj);
+        }
+        if let Some(ob) = self.self_referential_objects().first() {
+            return Err(format!("Object ν{} refers to itself", ob));
+        }
+        Ok(())
+    }
+
+    /// Print all objects and all live baskets, one per line, in order
+    /// to inspect the state later or bring it back with `restore`.
+    pub fn snapshot(&self) -> String {
+        self.objects()
+            .map(|(ob, obj)| format!("ν{}(𝜋) ↦ {}", ob, obj))
+            .chain(
+                self.live_baskets()
+                    .map(|(bk, bsk)| format!("β{} {}", bk, bsk)),
+            )
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
+
+    /// Make an Emu from the text made by `snapshot`, with the same
+    /// objects and baskets.
+    pub fn restore(s: &str) -> Result<Emu, String> {
+        let mut emu = Emu::empty();
+        emu.baskets[ROOT_BK as usize] = Basket::empty();
+        let (baskets, objects): (Vec<&str>, Vec<&str>) = s
+            .lines()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .partition(|t| t.starts_with('β'));
+        emu.load(&objects.join("\n"))?;
+        for line in baskets {
+            let (bk, bsk) = line
+                .split_once(' ')
+                .ok_or(format!("Can't parse the line '{}'", line))?;
+            let bk: Bk = bk['β'.len_utf8()..]
+                .parse()
+                .map_err(|e| format!("Can't parse the basket number in '{}': {}", line, e))?;
+            if bk < 0 || bk as usize >= emu.baskets.len() {
+                return Err(format!("The basket β{} is out of capacity", bk));
+            }
+            if !emu.basket(bk).is_empty() {
+                return Err(format!("The basket β{} is given twice", bk));
+            }
+            emu.inject(bk, Basket::from_str(bsk)?);
+        }
+        Ok(emu)
+    }
+
     /// Add an additional object
     pub fn put(&mut self, ob: Ob, obj: Object) -> &mut Emu {
         assert!(
@@ -138,6 +716,96 @@ impl Emu {
         self
     }
 
+    /// Find the lowest position not yet occupied by an object.
+    pub fn alloc_object(&self) -> Ob {
+        self.objects
+            .iter()
+            .position(|obj| obj.is_empty())
+            .unwrap_or_else(|| {
+                panic!(
+                    "No more empty objects left in the pool of {}",
+                    self.objects.len()
+                )
+            })
+    }
+
+    /// Parse every line of the program, without loading it, and
+    /// report all the errors found, together with line numbers.
+    pub fn check_all(s: &str) -> Vec<(usize, String)> {
+        s.strip_prefix('\u{feff}')
+            .unwrap_or(s)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(i, line)| {
+                Emu::parse_line(line.trim(), false)
+                    .err()
+                    .map(|e| (i + 1, e))
+            })
+            .collect()
+    }
+
+    /// Parse one line of a program, like `ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧`.
+    fn parse_line(line: &str, lenient: bool) -> Result<(Ob, Object), String> {
+        let re_line = Regex::new("^ν(\\d+)\\(𝜋\\) ↦ (⟦.*⟧(?:\\s*#.*)?)$").unwrap();
+        let caps = re_line
+            .captures(line)
+            .ok_or(format!("Can't parse the line '{}'", line))?;
+        let ob = caps[1]
+            .parse()
+            .map_err(|e| format!("Can't parse the object number in '{}': {}", line, e))?;
+        let obj = if lenient {
+            Object::from_str_lenient(&caps[2])?
+        } else {
+            Object::from_str(&caps[2])?
+        };
+        Ok((ob, obj))
+    }
+
+    /// Change the data of the data object, like an input of the program.
+    pub fn set_data(&mut self, ob: Ob, d: Data) -> Result<(), String> {
+        match self.objects.get_mut(ob) {
+            Some(obj) if obj.delta.is_some() => {
+                obj.delta = Some(d);
+                self.memo.remove(&ob);
+                Ok(())
+            }
+            _ => Err(format!("Object ν{} is not a data one", ob)),
+        }
+    }
+
+    /// Add an object to the lowest free position and return it.
+    pub fn put_next(&mut self, obj: Object) -> Ob {
+        let ob = self.alloc_object();
+        self.put(ob, obj);
+        ob
+    }
+
+    /// Iterate over all non-empty objects, together with their positions.
+    pub fn objects(&self) -> impl Iterator<Item = (Ob, &Object)> {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|(_, obj)| !obj.is_empty())
+    }
+
+    /// Get the object to change it in place, or `None` if the position
+    /// is out of the pool. Its memoized value, if any, is forgotten.
+    pub fn object_mut(&mut self, ob: Ob) -> Option<&mut Object> {
+        self.memo.remove(&ob);
+        self.objects.get_mut(ob)
+    }
+
+    /// Iterate over all non-empty objects, letting them be changed
+    /// in place. All memoized values are forgotten.
+    pub fn objects_mut(&mut self) -> impl Iterator<Item = (Ob, &mut Object)> {
+        self.memo.clear();
+        self.objects
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, obj)| !obj.is_empty())
+    }
+
     /// Inject a basket
     pub fn inject(&mut self, bk: Bk, bsk: Basket) -> &mut Emu {
         assert!(
@@ -145,18 +813,263 @@ impl Emu {
             "The basket β{} already occupied",
             bk
         );
+        for kid in bsk.kids.values() {
+            if let Kid::Wait(b, _) = kid {
+                *self.refs.entry(*b).or_insert(0) += 1;
+            }
+        }
+        if self.unfinished_atom(&bsk) {
+            *self.feeders.entry(bsk.psi).or_insert(0) += 1;
+        }
         self.baskets[bk as usize] = bsk;
         self
     }
 
+    /// Move all live baskets to the beginning of the pool, keeping
+    /// their order and fixing all references between them. It must be
+    /// called between cycles only. A basket, which outlived its ξ, like
+    /// a constant one, is dropped if nobody waits for it, since its
+    /// attributes can't be found anymore; if somebody does, its ξ becomes
+    /// the root basket. Kids that wait for or need a dropped basket are
+    /// requested again, to be found from scratch.
+    pub fn compact(&mut self) {
+        while let Some(bk) = self.orphan() {
+            let orphan = std::mem::replace(&mut self.baskets[bk as usize], Basket::empty());
+            for kid in orphan.kids.values() {
+                if let Kid::Wait(b, _) = kid {
+                    if let Some(c) = self.refs.get_mut(b) {
+                        *c -= 1;
+                    }
+                }
+            }
+        }
+        let moves: HashMap<Bk, Bk> = self
+            .live_baskets()
+            .enumerate()
+            .map(|(pos, (bk, _))| (bk, pos as Bk))
+            .collect();
+        let total = self.baskets.len();
+        let mut baskets = vec![];
+        for bsk in std::mem::take(&mut self.baskets) {
+            if bsk.is_empty() {
+                continue;
+            }
+            let mut bsk = bsk;
+            bsk.psi = *moves.get(&bsk.psi).unwrap_or(&ROOT_BK);
+            for kid in bsk.kids.values_mut() {
+                let target = match kid {
+                    Kid::Wait(b, _) => Some(b),
+                    Kid::Need(_, psi) => Some(psi),
+                    _ => None,
+                };
+                if let Some(b) = target {
+                    match moves.get(b) {
+                        Some(m) => *b = *m,
+                        None => *kid = Kid::Rqtd,
+                    }
+                }
+            }
+            baskets.push(bsk);
+        }
+        baskets.resize_with(total, Basket::empty);
+        self.baskets = baskets;
+        self.refs = self
+            .refs
+            .iter()
+            .filter_map(|(bk, c)| moves.get(bk).map(|b| (*b, *c)))
+            .collect();
+        let mut feeders = HashMap::new();
+        for (_, bsk) in self.live_baskets().filter(|(_, b)| self.unfinished_atom(b)) {
+            *feeders.entry(bsk.psi).or_insert(0) += 1;
+        }
+        self.feeders = feeders;
+        self.touched = self
+            .touched
+            .iter()
+            .filter_map(|(bk, loc)| moves.get(bk).map(|b| (*b, loc.clone())))
+            .collect();
+        self.unresolved = self
+            .unresolved
+            .iter()
+            .filter_map(|(bk, loc, e)| moves.get(bk).map(|b| (*b, loc.clone(), e.clone())))
+            .collect();
+    }
+
+    /// Find a basket, which nobody waits for, while its ξ is gone.
+    fn orphan(&self) -> Option<Bk> {
+        self.live_baskets()
+            .find(|(bk, bsk)| {
+                *bk != ROOT_BK && self.basket(bsk.psi).is_empty() && self.refs(*bk) == 0
+            })
+            .map(|(bk, _)| bk)
+    }
+
+    /// Let the emulator know that an atom's arithmetic has just
+    /// overflowed in the basket, to be counted in `Perf`.
+    pub fn overflow(&mut self, bk: Bk) {
+        self.overflows += 1;
+        self.warn(Warning::Overflow { bk });
+    }
+
+    /// The problems found since the last `reset()`, in the order
+    /// they happened.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    fn warn(&mut self, warning: Warning) {
+        warn!("{}", warning);
+        self.warnings.push(warning);
+    }
+
+    /// How many kids in other baskets are waiting for this one.
+    pub fn refs(&self, bk: Bk) -> usize {
+        *self.refs.get(&bk).unwrap_or(&0)
+    }
+
+    /// How many atoms, which are not finished yet, have this basket
+    /// as their ξ and may still read their operands through `𝜋`.
+    pub fn feeders(&self, bk: Bk) -> usize {
+        *self.feeders.get(&bk).unwrap_or(&0)
+    }
+
+    /// The basket belongs to an atom, which is not finished yet.
+    pub(crate) fn unfinished_atom(&self, bsk: &Basket) -> bool {
+        self.object(bsk.ob).lambda.is_some()
+            && !matches!(bsk.kids.get(&Loc::Phi), Some(Kid::Dtzd(_)))
+    }
+
+    /// Iterate over all baskets that are not empty, together
+    /// with their positions in the pool.
+    pub fn live_baskets(&self) -> impl Iterator<Item = (Bk, &Basket)> {
+        self.baskets
+            .iter()
+            .enumerate()
+            .filter(|(_, bsk)| !bsk.is_empty())
+            .map(|(bk, bsk)| (bk as Bk, bsk))
+    }
+
+    /// For each live basket, find the locs that are requested,
+    /// but not yet dataized, like `𝛼0→?`.
+    pub fn pending(&self) -> Vec<(Bk, Vec<Loc>)> {
+        self.live_baskets()
+            .map(|(bk, bsk)| {
+                (
+                    bk,
+                    bsk.kids
+                        .iter()
+                        .filter(|(_, kid)| matches!(kid, Kid::Rqtd))
+                        .map(|(loc, _)| loc.clone())
+                        .sorted_by_key(|loc| loc.to_string())
+                        .collect::<Vec<Loc>>(),
+                )
+            })
+            .filter(|(_, locs)| !locs.is_empty())
+            .collect()
+    }
+
+    /// Put a kid into the basket, keeping the counters of
+    /// references between baskets up to date.
+    fn put_kid(&mut self, bk: Bk, loc: Loc, kid: Kid) {
+        if let (Loc::Phi, Kid::Dtzd(d)) = (&loc, &kid) {
+            let ob = self.basket(bk).ob;
+            if self.object(ob).constant && self.opts.contains(&Opt::MemoizeConstants) {
+                self.memo.insert(ob, *d);
+            }
+        }
+        if let (Kid::Dtzd(d), Some(f)) = (&kid, self.dataized_listener.as_mut()) {
+            f(bk, &loc, *d);
+        }
+        if let Kid::Wait(b, _) = kid {
+            *self.refs.entry(b).or_insert(0) += 1;
+        }
+        if loc == Loc::Phi && matches!(kid, Kid::Dtzd(_)) && self.unfinished_atom(self.basket(bk)) {
+            let psi = self.basket(bk).psi;
+            if let Some(c) = self.feeders.get_mut(&psi) {
+                *c -= 1;
+            }
+        }
+        if let Some(Kid::Wait(b, _)) = self.baskets[bk as usize].kids.insert(loc, kid) {
+            if let Some(c) = self.refs.get_mut(&b) {
+                *c -= 1;
+            }
+        }
+    }
+
+    /// Compare with another emulator and list the objects and baskets
+    /// that differ, one line per difference.
+    pub fn diff(&self, other: &Emu) -> Vec<String> {
+        let mut diffs = vec![];
+        for ob in 0..self.objects.len().max(other.objects.len()) {
+            let mine = self.objects.get(ob).filter(|o| !o.is_empty());
+            let theirs = other.objects.get(ob).filter(|o| !o.is_empty());
+            match (mine, theirs) {
+                (Some(a), None) => diffs.push(format!("ν{} {} is absent in the other", ob, a)),
+                (None, Some(b)) => diffs.push(format!("ν{} {} is absent here", ob, b)),
+                (Some(a), Some(b)) if a.to_string() != b.to_string() => {
+                    diffs.push(format!("ν{} {} ≠ {}", ob, a, b))
+                }
+                _ => {}
+            }
+        }
+        for bk in 0..self.baskets.len().max(other.baskets.len()) {
+            let mine = self.baskets.get(bk).filter(|b| !b.is_empty());
+            let theirs = other.baskets.get(bk).filter(|b| !b.is_empty());
+            match (mine, theirs) {
+                (Some(a), None) => diffs.push(format!("β{} {} is absent in the other", bk, a)),
+                (None, Some(b)) => diffs.push(format!("β{} {} is absent here", bk, b)),
+                (Some(a), Some(b)) if a.to_string() != b.to_string() => {
+                    diffs.push(format!("β{} {} ≠ {}", bk, a, b))
+                }
+                _ => {}
+            }
+        }
+        diffs
+    }
+
+    /// Read the kid, like `read` does, but also remember it as touched
+    /// if it gets requested, so that with `Opt::PrioritizeTouched` it
+    /// is found and copied in the same cycle.
+    pub fn read_or_request(&mut self, bk: Bk, loc: Loc) -> Option<Data> {
+        let empty = matches!(self.basket(bk).kids.get(&loc), Some(Kid::Empt));
+        if empty && self.opts.contains(&Opt::PrioritizeTouched) {
+            self.touched.push((bk, loc.clone()));
+        }
+        self.read(bk, loc)
+    }
+
+    /// Read the `Δ` of the object that the loc of the basket refers to,
+    /// without waiting for it to be copied into a basket. It's `None`
+    /// if the object is not a data one, then `read` has to be used.
+    pub fn read_delta(&self, bk: Bk, loc: Loc) -> Option<Data> {
+        let (locator, _) = self.object(self.basket(bk).ob).attrs.get(&loc)?;
+        let (ob, _, _) = self.search(bk, locator).ok()?;
+        self.object(ob).delta
+    }
+
     /// Read data if available.
     pub fn read(&mut self, bk: Bk, loc: Loc) -> Option<Data> {
+        self.reads += 1;
+        if let Some(max) = self.max_reads {
+            if self.reads > max {
+                trace!(
+                    "The read budget exceeded at β{}.{}: {} reads, while only {} allowed",
+                    bk,
+                    loc,
+                    self.reads,
+                    max
+                );
+                self.halted.get_or_insert(Outcome::TooManyReads {
+                    reads: self.reads,
+                    max,
+                });
+                return None;
+            }
+        }
         match self.basket(bk).kids.get(&loc) {
             None => panic!("Can't find {} in β{}:\n{}", loc, bk, self),
             Some(Kid::Empt) => {
-                let _ = &self.baskets[bk as usize]
-                    .kids
-                    .insert(loc.clone(), Kid::Rqtd);
+                self.put_kid(bk, loc.clone(), Kid::Rqtd);
                 trace!("read(β{}, {}): was empty, requested", bk, loc);
                 None
             }
@@ -0,0 +1,282 @@
+// Copyright (c) 2022 Yegor Bugayenko
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included
+// in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NON-INFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::atom;
+use crate::emu::{Emu, Termination, ROOT_OB};
+use crate::loc::Loc;
+use crate::object::Ob;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+impl Emu {
+    /// Find objects with attributes pointing directly to themselves,
+    /// like `ν2 ↦ ⟦ 𝜑 ↦ ν2 ⟧`.
+    pub fn self_referential_objects(&self) -> Vec<Ob> {
+        self.objects()
+            .filter(|(ob, _)| self.links(*ob).iter().any(|(_, to)| to == ob))
+            .map(|(ob, _)| ob)
+            .collect()
+    }
+
+    /// Hash all objects of the program, ignoring their labels, with
+    /// FNV-1a, which stays the same between runs and Rust versions.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for (ob, obj) in self.objects() {
+            let mut plain = obj.clone();
+            plain.label = None;
+            for byte in format!("ν{} {}\n", ob, plain).bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+
+    /// Find objects that can't be reached from the root one, following
+    /// all references to objects in their attributes, like `𝜑 ↦ ν3`.
+    pub fn unreachable_objects(&self) -> Vec<Ob> {
+        let mut seen = HashSet::new();
+        let mut todo = vec![ROOT_OB];
+        while let Some(ob) = todo.pop() {
+            if !seen.insert(ob) {
+                continue;
+            }
+            if let Some(obj) = self.objects.get(ob) {
+                for (locator, _) in obj.attrs.values() {
+                    for loc in locator.to_vec() {
+                        if let Loc::Obj(to) = loc {
+                            todo.push(to);
+                        }
+                    }
+                }
+            }
+        }
+        self.objects()
+            .map(|(ob, _)| ob)
+            .filter(|ob| !seen.contains(ob))
+            .collect()
+    }
+
+    /// Find attributes that refer to objects, which are not defined,
+    /// like `𝜑 ↦ ν7` when there is no `ν7`. Gaps between objects
+    /// that nobody refers to are fine.
+    pub fn dangling_references(&self) -> Vec<(Ob, Loc, Ob)> {
+        self.objects()
+            .flat_map(|(ob, obj)| {
+                obj.attrs
+                    .iter()
+                    .flat_map(|(loc, (locator, _))| {
+                        locator.to_vec().into_iter().filter_map(move |l| match l {
+                            Loc::Obj(to) => Some((ob, loc.clone(), to)),
+                            _ => None,
+                        })
+                    })
+                    .collect::<Vec<(Ob, Loc, Ob)>>()
+            })
+            .filter(|(_, _, to)| self.objects.get(*to).is_none_or(|o| o.is_empty()))
+            .sorted_by_key(|(ob, loc, _)| (*ob, loc.to_string()))
+            .collect()
+    }
+
+    /// Find atoms, which don't have all the operands their
+    /// lambdas need, like `int-add` without `𝛼0`.
+    pub fn lint_atoms(&self) -> Vec<(Ob, String)> {
+        let mut found = vec![];
+        for (ob, obj) in self.objects() {
+            if let Some((name, _)) = &obj.lambda {
+                for loc in atom::operands(obj).unwrap_or_default() {
+                    if !obj.attrs.contains_key(&loc) {
+                        found.push((
+                            ob,
+                            format!(
+                                "The atom {} needs {}, but ν{} doesn't have it",
+                                name, loc, ob
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// List all direct references between objects, like `𝜑 ↦ ν3`,
+    /// as edges from the object through its attribute to another one,
+    /// ordered by the source object.
+    pub fn object_graph(&self) -> Vec<(Ob, Loc, Ob)> {
+        self.objects()
+            .flat_map(|(ob, _)| {
+                self.links(ob)
+                    .into_iter()
+                    .map(move |(loc, to)| (ob, loc, to))
+            })
+            .sorted_by_key(|(ob, loc, _)| (*ob, loc.to_string()))
+            .collect()
+    }
+
+    /// Find the length of the longest chain of direct references between
+    /// objects, starting from the root one. This is the least number of
+    /// baskets that have to wait for each other during dataization.
+    pub fn critical_path(&self) -> Result<usize, String> {
+        self.depth(ROOT_OB, &mut HashMap::new(), &mut vec![])
+    }
+
+    fn depth(
+        &self,
+        ob: Ob,
+        depths: &mut HashMap<Ob, usize>,
+        path: &mut Vec<Ob>,
+    ) -> Result<usize, String> {
+        if let Some(d) = depths.get(&ob) {
+            return Ok(*d);
+        }
+        if path.contains(&ob) {
+            return Err(format!(
+                "There is a cycle: {} → ν{}",
+                path.iter().map(|o| format!("ν{}", o)).join(" → "),
+                ob
+            ));
+        }
+        path.push(ob);
+        let mut depth = 0;
+        for (_, to) in self.links(ob) {
+            depth = depth.max(self.depth(to, depths, path)? + 1);
+        }
+        path.pop();
+        depths.insert(ob, depth);
+        Ok(depth)
+    }
+
+    /// Guess, without running the program, whether its dataization
+    /// finishes. Without cycles between objects, reachable from the root
+    /// one, it surely does. A cycle without `bool-if` may go on forever.
+    /// A cycle that goes through `𝛼1` of a `bool-if` with an `int-less`
+    /// condition and passes a decremented argument, like a recursion
+    /// over a counter does, is believed to finish. Anything else is unknown.
+    pub fn likely_terminates(&self) -> Termination {
+        let mut reach: HashMap<Ob, HashSet<Ob>> = HashMap::new();
+        for ob in self.reachable(ROOT_OB).into_iter().chain([ROOT_OB]) {
+            reach.insert(ob, self.reachable(ob));
+        }
+        let cycles: HashSet<Vec<Ob>> = reach
+            .iter()
+            .filter(|(ob, to)| to.contains(ob))
+            .map(|(ob, to)| {
+                to.iter()
+                    .filter(|o| reach[o].contains(ob))
+                    .copied()
+                    .sorted()
+                    .collect()
+            })
+            .collect();
+        let mut verdict = Termination::Terminates;
+        for cycle in cycles {
+            if !cycle.iter().any(|ob| self.atom_of(*ob) == Some("bool-if")) {
+                return Termination::MayNotTerminate;
+            }
+            let guarded = cycle.iter().any(|ob| self.guards(*ob, &cycle));
+            if !guarded || !cycle.iter().any(|ob| self.decrements(*ob)) {
+                verdict = Termination::Unknown;
+            }
+        }
+        verdict
+    }
+
+    /// All objects reachable from this one by direct references,
+    /// including itself only if it's in a cycle.
+    fn reachable(&self, ob: Ob) -> HashSet<Ob> {
+        let mut seen = HashSet::new();
+        let mut todo: Vec<Ob> = self.links(ob).into_iter().map(|(_, to)| to).collect();
+        while let Some(next) = todo.pop() {
+            if seen.insert(next) {
+                todo.extend(self.links(next).into_iter().map(|(_, to)| to));
+            }
+        }
+        seen
+    }
+
+    fn atom_of(&self, ob: Ob) -> Option<&str> {
+        self.objects
+            .get(ob)?
+            .lambda
+            .as_ref()
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The object is a `bool-if` with an `int-less` condition, which
+    /// leaves the cycle in `𝛼0` and stays in it in `𝛼1`.
+    fn guards(&self, ob: Ob, cycle: &[Ob]) -> bool {
+        if self.atom_of(ob) != Some("bool-if") {
+            return false;
+        }
+        let links: HashMap<Loc, Ob> = self.links(ob).into_iter().collect();
+        match (
+            links.get(&Loc::Rho),
+            links.get(&Loc::Attr(0)),
+            links.get(&Loc::Attr(1)),
+        ) {
+            (Some(cond), Some(exit), Some(next)) => {
+                self.atom_of(*cond) == Some("int-less")
+                    && !cycle.contains(exit)
+                    && cycle.contains(next)
+            }
+            _ => false,
+        }
+    }
+
+    /// The object passes an argument, which is `int-dec` or `int-sub`
+    /// of a positive constant, like `𝛼0 ↦ ν8` with
+    /// `ν8 ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν7 ⟧` and `ν7 ↦ ⟦ Δ ↦ 0x0001 ⟧`.
+    fn decrements(&self, ob: Ob) -> bool {
+        self.links(ob)
+            .into_iter()
+            .filter(|(loc, _)| *loc != Loc::Phi)
+            .any(|(_, to)| match self.atom_of(to) {
+                Some("int-dec") => true,
+                Some("int-sub") => self.links(to).iter().any(|(loc, by)| {
+                    *loc == Loc::Attr(0)
+                        && self
+                            .objects
+                            .get(*by)
+                            .and_then(|o| o.delta)
+                            .is_some_and(|d| d > 0)
+                }),
+                _ => false,
+            })
+    }
+
+    /// Attributes of the object that point directly to other
+    /// objects, like `𝜑 ↦ ν3`, ordered by the object they point to.
+    fn links(&self, ob: Ob) -> Vec<(Loc, Ob)> {
+        match self.objects.get(ob) {
+            None => vec![],
+            Some(obj) => obj
+                .attrs
+                .iter()
+                .filter_map(|(loc, (locator, _))| match locator.loc(0) {
+                    Some(Loc::Obj(to)) if locator.len() == 1 => Some((loc.clone(), *to)),
+                    _ => None,
+                })
+                .sorted_by_key(|(loc, to)| (*to, loc.to_string()))
+                .collect(),
+        }
+    }
+}
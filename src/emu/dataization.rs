@@ -20,25 +20,155 @@
 
 use crate::basket::{Bk, Kid};
 use crate::data::Data;
-use crate::emu::{Emu, Opt, ROOT_BK};
+use crate::emu::{Emu, Opt, Outcome, StuckReport, Warning, ROOT_BK, ROOT_OB};
 use crate::loc::Loc;
+use crate::locator::Locator;
+use crate::object::{Ob, Object};
 use crate::perf::Perf;
+use itertools::Itertools;
 use log::debug;
+use std::panic::{self, AssertUnwindSafe};
 use std::time::Instant;
 
-const MAX_CYCLES: usize = 65536;
+pub const MAX_CYCLES: usize = 65536;
 
 impl Emu {
-    /// Dataize the first object.
+    /// Dataize the first object. If it's already dataized, the value
+    /// and the `Perf` of that run are returned again, call `reset()`
+    /// to dataize it from scratch.
     pub fn dataize(&mut self) -> (Data, Perf) {
+        self.try_dataize().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Dataize the first object, like `dataize` does, but return an error
+    /// instead of panicking, if there is no root object or the emulator
+    /// gets stuck or runs for too long.
+    pub fn try_dataize(&mut self) -> Result<(Data, Perf), String> {
+        if self.object(ROOT_OB).is_empty() {
+            return Err(format!("There is no root object ν{} to dataize", ROOT_OB));
+        }
+        let stuck = self.opts.contains(&Opt::StopWhenStuck);
+        let endless = self.opts.contains(&Opt::StopWhenTooManyCycles);
+        match self.run(stuck, endless) {
+            (Outcome::Value(d), perf) => Ok((d, perf)),
+            (outcome @ Outcome::TooManyCycles(_), _) => Err(format!("{}:\n{}", outcome, self)),
+            (outcome, _) => Err(outcome.to_string()),
+        }
+    }
+
+    /// Dataize the first object and report how it went, instead of
+    /// panicking when the emulator gets stuck or runs for too long.
+    pub fn evaluate(&mut self) -> Outcome {
+        if self.object(ROOT_OB).is_empty() {
+            return Outcome::Stuck(format!("There is no root object ν{} to dataize", ROOT_OB));
+        }
+        self.run(true, true).0
+    }
+
+    /// Dataize the first object, expecting it to get stuck, which is
+    /// good for negative tests. It's an error if it doesn't.
+    pub fn expect_stuck(&mut self) -> Result<StuckReport, String> {
+        if self.object(ROOT_OB).is_empty() {
+            return Err(format!("There is no root object ν{} to dataize", ROOT_OB));
+        }
+        match self.run(true, true) {
+            (Outcome::Stuck(message), perf) => Ok(StuckReport {
+                message,
+                cycles: perf.cycles,
+                blocked: self.blocked(),
+            }),
+            (Outcome::Value(d), _) => Err(format!(
+                "Expected to get stuck, but dataized to 0x{:04X}",
+                d
+            )),
+            (outcome, _) => Err(format!("Expected to get stuck, but: {}", outcome)),
+        }
+    }
+
+    /// Dataize the first object, but make no more than the given
+    /// number of cycles. The outcome is `TooManyCycles` if it's not
+    /// ready by then; the next call continues from where this one stopped.
+    pub fn dataize_bounded(&mut self, max_cycles: usize) -> (Outcome, Perf) {
+        let mut perf = Perf::new();
+        while perf.cycles < max_cycles {
+            if let Some(outcome) = self.advance(&mut perf) {
+                return (outcome, perf);
+            }
+        }
+        (Outcome::TooManyCycles(perf.cycles), perf)
+    }
+
+    /// Make one cycle of dataization of the first object. The outcome
+    /// is there if the value is ready or dataization had to stop.
+    pub fn advance(&mut self, perf: &mut Perf) -> Option<Outcome> {
+        self.step(perf);
+        if let Some(outcome) = self.halted.take() {
+            return Some(outcome);
+        }
+        match self.basket(ROOT_BK).kids.get(&Loc::Phi) {
+            Some(Kid::Dtzd(d)) => Some(Outcome::Value(*d)),
+            _ => None,
+        }
+    }
+
+    /// Dataize the first object and return its value as bytes in
+    /// network order: big-endian, two bytes, since `Data` is `i16`.
+    pub fn result_bytes(&mut self) -> Result<Vec<u8>, String> {
+        self.evaluate()
+            .into_result()
+            .map(|d| d.to_be_bytes().to_vec())
+    }
+
+    /// Dataize every object, except atoms, as if it was the root one,
+    /// each in its own copy of this emulator.
+    pub fn dataize_all(&self) -> Vec<(Ob, Result<Data, String>)> {
+        self.objects()
+            .filter(|(_, obj)| obj.lambda.is_none())
+            .map(|(ob, _)| {
+                let mut emu = self.clone();
+                emu.reset();
+                if ob != ROOT_OB {
+                    emu.objects[ROOT_OB] =
+                        Object::open().with(Loc::Phi, Locator::from_loc(Loc::Obj(ob)), false);
+                }
+                let ret = panic::catch_unwind(AssertUnwindSafe(|| emu.evaluate()))
+                    .map_err(|e| {
+                        e.downcast_ref::<String>()
+                            .cloned()
+                            .or_else(|| e.downcast_ref::<&str>().map(|s| s.to_string()))
+                            .unwrap_or_else(|| "Unknown failure".to_string())
+                    })
+                    .and_then(Outcome::into_result);
+                (ob, ret)
+            })
+            .collect()
+    }
+
+    fn run(&mut self, stuck: bool, endless: bool) -> (Outcome, Perf) {
+        if let (Some(Kid::Dtzd(d)), Some(perf)) =
+            (self.basket(ROOT_BK).kids.get(&Loc::Phi), &self.finished)
+        {
+            debug!(
+                "dataize() -> 0x{:04X}, already done, use reset() to start over",
+                *d
+            );
+            return (Outcome::Value(*d), perf.clone());
+        }
         let mut cycles = 0;
         let mut perf = Perf::new();
         let time = Instant::now();
+        let snapshots = self.opts.contains(&Opt::LogSnapshots);
+        let counting = snapshots || stuck;
+        let mut before = 0;
         loop {
-            let before = perf.total_hits();
-            self.cycle(&mut perf);
-            perf.peak(self.baskets.iter().filter(|bsk| !bsk.is_empty()).count());
-            if self.opts.contains(&Opt::LogSnapshots) {
+            if counting {
+                before = perf.total_hits();
+            }
+            self.step(&mut perf);
+            if let Some(outcome) = self.halted.take() {
+                return (outcome, perf);
+            }
+            if snapshots {
                 debug!(
                     "dataize() +{} hits in cycle #{}:\n{}",
                     perf.total_hits() - before,
@@ -46,15 +176,24 @@ impl Emu {
                     self
                 );
             }
-            if self.opts.contains(&Opt::StopWhenStuck) && before == perf.total_hits() {
-                panic!(
-                    "We are stuck, no hits after {}, in the recent cycle #{}:\n{}",
-                    perf.total_hits(),
+            if stuck && before == perf.total_hits() {
+                let deleted = self.deleted_operand();
+                if let Some((bk, loc, _)) = &deleted {
+                    self.warn(Warning::DeletedOperand {
+                        bk: *bk,
+                        loc: loc.clone(),
+                    });
+                }
+                let msg = format!(
+                    "We are stuck, {}, in the recent cycle #{}:\n{}",
+                    deleted
+                        .map(|(_, _, m)| m)
+                        .unwrap_or(format!("no hits after {}", perf.total_hits())),
                     cycles,
                     self
                 );
+                return (Outcome::Stuck(msg), perf);
             }
-            perf.cycles += 1;
             if let Some(Kid::Dtzd(d)) = self.basket(ROOT_BK).kids.get(&Loc::Phi) {
                 debug!(
                     "dataize() -> 0x{:04X} in {:?}\n{}\n{}",
@@ -63,21 +202,31 @@ impl Emu {
                     perf,
                     self
                 );
-                return (*d, perf);
+                let d = *d;
+                self.finished = Some(perf.clone());
+                return (Outcome::Value(d), perf);
             }
             cycles += 1;
-            if self.opts.contains(&Opt::StopWhenTooManyCycles) && cycles > MAX_CYCLES {
-                panic!(
-                    "Too many cycles ({}), most probably endless recursion:\n{}",
-                    cycles, self
-                );
+            if endless && cycles > self.max_cycles {
+                return (Outcome::TooManyCycles(cycles), perf);
             }
         }
     }
 
+    /// Make one cycle of dataization, moving all baskets one step
+    /// forward, and count it in the given `Perf`.
+    pub fn step(&mut self, perf: &mut Perf) {
+        self.cycle(perf);
+        perf.peak(self.baskets.iter().filter(|bsk| !bsk.is_empty()).count());
+        perf.cycles += 1;
+    }
+
     fn cycle(&mut self, perf: &mut Perf) {
         self.cycle_one(perf, |s, p, bk| s.copy(p, bk));
         self.cycle_one(perf, |s, p, bk| s.delegate(p, bk));
+        if self.opts.contains(&Opt::PrioritizeTouched) {
+            self.prioritize(perf);
+        }
         if !self.opts.contains(&Opt::DontDelete) {
             self.cycle_one(perf, |s, p, bk| s.delete(p, bk));
         }
@@ -90,6 +239,21 @@ impl Emu {
         });
     }
 
+    /// Find the kids touched by atoms, make baskets for them, and copy
+    /// data into those baskets right away, without waiting for the
+    /// next cycle.
+    fn prioritize(&mut self, perf: &mut Perf) {
+        for (bk, loc) in std::mem::take(&mut self.touched) {
+            self.find(perf, bk, loc.clone());
+            self.new(perf, bk, loc.clone());
+            if let Some(Kid::Wait(nbk, _)) = self.basket(bk).kids.get(&loc) {
+                let nbk = *nbk;
+                self.copy(perf, nbk);
+                self.propagate(perf, nbk, Loc::Phi);
+            }
+        }
+    }
+
     fn cycle_one(&mut self, perf: &mut Perf, f: fn(&mut Emu, &mut Perf, Bk)) {
         for i in 0..self.baskets.len() {
             let bk = i as Bk;
@@ -100,6 +264,41 @@ impl Emu {
         }
     }
 
+    /// Find an atom, which waits for an operand in a basket
+    /// that is already deleted.
+    fn deleted_operand(&self) -> Option<(Bk, Loc, String)> {
+        self.baskets
+            .iter()
+            .enumerate()
+            .filter(|(_, bsk)| !bsk.is_empty() && self.object(bsk.ob).lambda.is_some())
+            .find_map(|(bk, bsk)| {
+                bsk.kids.iter().find_map(|(loc, kid)| match kid {
+                    Kid::Wait(b, _) if self.basket(*b).is_empty() => Some((
+                        bk as Bk,
+                        loc.clone(),
+                        format!(
+                            "atom operand was deleted: β{}.{} of ν{} waits for β{}",
+                            bk, loc, bsk.ob, b
+                        ),
+                    )),
+                    _ => None,
+                })
+            })
+    }
+
+    /// The kids of live baskets that are requested or waiting.
+    fn blocked(&self) -> Vec<(Bk, Loc)> {
+        self.live_baskets()
+            .flat_map(|(bk, bsk)| {
+                bsk.kids
+                    .iter()
+                    .filter(|(_, kid)| matches!(kid, Kid::Rqtd | Kid::Wait(_, _)))
+                    .map(move |(loc, _)| (bk, loc.clone()))
+            })
+            .sorted_by_key(|(bk, loc)| (*bk, loc.to_string()))
+            .collect()
+    }
+
     /// Take all locs from the given basket.
     fn locs(&self, bk: Bk) -> Vec<Loc> {
         let mut keys = vec![];
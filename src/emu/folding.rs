@@ -0,0 +1,157 @@
+// Copyright (c) 2022 Yegor Bugayenko
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included
+// in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NON-INFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::atom::Atom;
+use crate::data::Data;
+use crate::emu::{Emu, Outcome};
+use crate::loc::Loc;
+use crate::locator::Locator;
+use crate::object::{Ob, Object};
+use std::collections::{HashMap, HashSet};
+
+impl Emu {
+    /// Replace objects that call atoms with constant data only, like
+    /// `⟦ λ ↦ int-add, ρ ↦ ν1, 𝛼0 ↦ ν1 ⟧` where `ν1` is `⟦ Δ ↦ 0x002A ⟧`,
+    /// with the data the atoms return, until nothing else can be folded.
+    /// Objects that decorate such calls, like `⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1 ⟧`,
+    /// where `ν2` reads `𝜋.𝛼0`, are folded too. Objects whose attributes
+    /// others may reach, like `𝛼1` of `ν3` in `𝜋.𝛼0.𝛼1`, are never folded.
+    /// Returns how many objects were replaced.
+    pub fn fold_constants(&mut self) -> usize {
+        let mut total = 0;
+        loop {
+            let opened = self.opened();
+            let folds: Vec<(Ob, Data)> = self
+                .objects()
+                .filter(|(ob, _)| !opened.contains(ob))
+                .filter_map(|(ob, obj)| self.foldable(obj).map(|call| (ob, call)))
+                .filter_map(|(ob, (lambda, args))| self.call(lambda, &args).map(|d| (ob, d)))
+                .collect();
+            if folds.is_empty() {
+                break;
+            }
+            for (ob, d) in folds {
+                let mut obj = Object::dataic(d);
+                obj.label = self.object(ob).label.clone();
+                self.objects[ob] = obj;
+                self.memo.remove(&ob);
+                total += 1;
+            }
+        }
+        total
+    }
+
+    /// Find objects, whose attributes may be reached by locators, like
+    /// `ν3` in `⟦ 𝜑 ↦ ν6(ξ), 𝛼0 ↦ ν3 ⟧`, if `ν6` reads `𝜋.𝛼0.𝛼1`.
+    /// Only the objects that are referred to by bare `ν` and are never
+    /// looked into may be folded.
+    fn opened(&self) -> HashSet<Ob> {
+        let attrs: HashSet<&Loc> = self
+            .objects()
+            .flat_map(|(_, obj)| obj.attrs.values())
+            .flat_map(|(locator, _)| {
+                (1..locator.len()).filter_map(move |i| match locator.loc(i - 1) {
+                    Some(loc @ (Loc::Phi | Loc::Rho | Loc::Attr(_))) => Some(loc),
+                    _ => None,
+                })
+            })
+            .collect();
+        self.objects()
+            .flat_map(|(_, obj)| obj.attrs.iter())
+            .filter(|(loc, _)| attrs.contains(loc))
+            .filter_map(|(_, (locator, _))| match (locator.len(), locator.loc(0)) {
+                (1, Some(Loc::Obj(ob))) => Some(*ob),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Find the atom the object calls and the data of all its
+    /// attributes, if all of them are constant.
+    fn foldable(&self, obj: &Object) -> Option<((String, Atom), HashMap<Loc, Data>)> {
+        if obj.delta.is_some() {
+            return None;
+        }
+        if let Some(lambda) = &obj.lambda {
+            let args = obj
+                .attrs
+                .iter()
+                .map(|(loc, (locator, _))| self.constant(locator).map(|d| (loc.clone(), d)))
+                .collect::<Option<HashMap<Loc, Data>>>()?;
+            return Some((lambda.clone(), args));
+        }
+        let (locator, xi) = obj.attrs.get(&Loc::Phi)?;
+        let Some(Loc::Obj(f)) = locator.loc(0) else {
+            return None;
+        };
+        let func = self.objects.get(*f)?;
+        if !xi || locator.len() != 1 || func.delta.is_some() {
+            return None;
+        }
+        let lambda = func.lambda.as_ref()?;
+        let args = func
+            .attrs
+            .iter()
+            .map(|(loc, (locator, _))| {
+                let d = match (locator.len(), locator.loc(0), locator.loc(1)) {
+                    (2, Some(Loc::Pi), Some(attr)) => self.constant(&obj.attrs.get(attr)?.0),
+                    _ => self.constant(locator),
+                };
+                d.map(|d| (loc.clone(), d))
+            })
+            .collect::<Option<HashMap<Loc, Data>>>()?;
+        Some((lambda.clone(), args))
+    }
+
+    /// The data of the object the locator points to, if it's
+    /// a data object, like `ν1`.
+    fn constant(&self, locator: &Locator) -> Option<Data> {
+        match (locator.len(), locator.loc(0)) {
+            (1, Some(Loc::Obj(ob))) => self.objects.get(*ob)?.delta,
+            _ => None,
+        }
+    }
+
+    /// Dataize the atom with these arguments in a separate emulator,
+    /// with the same options. Nothing is returned if the atom doesn't
+    /// finish cleanly, like on overflow or division by zero, since then
+    /// it's up to dataization to deal with it.
+    fn call(&self, lambda: (String, Atom), args: &HashMap<Loc, Data>) -> Option<Data> {
+        if lambda.0 == "int-div" && args.get(&Loc::Attr(0)) == Some(&0) {
+            return None;
+        }
+        let mut emu = Emu::with_capacity(args.len() + 2, args.len() + 2);
+        emu.opts = self.opts.clone();
+        emu.put(
+            0,
+            Object::open().with(Loc::Phi, Locator::from_loc(Loc::Obj(1)), false),
+        );
+        let mut atom = Object::atomic(lambda.0, lambda.1);
+        for (i, (loc, d)) in args.iter().enumerate() {
+            emu.put(i + 2, Object::dataic(*d));
+            atom = atom.with(loc.clone(), Locator::from_loc(Loc::Obj(i + 2)), false);
+        }
+        emu.put(1, atom);
+        match emu.evaluate() {
+            Outcome::Value(d) if emu.warnings().is_empty() => Some(d),
+            _ => None,
+        }
+    }
+}
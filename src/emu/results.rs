@@ -0,0 +1,72 @@
+// Copyright (c) 2022 Yegor Bugayenko
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included
+// in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NON-INFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::basket::{Bk, Kid};
+use crate::emu::{Emu, ResultNode, ROOT_BK};
+use crate::loc::Loc;
+use itertools::Itertools;
+
+impl Emu {
+    /// Make a tree of baskets, starting from the root one, with the
+    /// data they have. Deleted baskets are seen as leaves, so it's
+    /// better to dataize with `Opt::DontDelete` before calling this.
+    pub fn result_tree(&self) -> Result<ResultNode, String> {
+        self.result_node(ROOT_BK, &mut vec![])
+    }
+
+    fn result_node(&self, bk: Bk, path: &mut Vec<Bk>) -> Result<ResultNode, String> {
+        path.push(bk);
+        let bsk = self.basket(bk);
+        let obj = self.object(bsk.ob);
+        let mut children = vec![];
+        for (loc, kid) in bsk.kids.iter().sorted_by_key(|(loc, _)| loc.to_string()) {
+            let (locator, advice) = match obj.attrs.get(loc) {
+                Some(attr) => attr,
+                None => continue,
+            };
+            let (tob, psi, _) = self.search(bk, locator)?;
+            let tpsi = if *advice { bk } else { psi };
+            let live = self.baskets.iter().position(|b| {
+                !b.is_empty() && b.ob == tob && (b.psi == tpsi || self.object(tob).delta.is_some())
+            });
+            let node = match live {
+                Some(pos) if !path.contains(&(pos as Bk)) => self.result_node(pos as Bk, path)?,
+                _ => ResultNode {
+                    ob: tob,
+                    data: match kid {
+                        Kid::Dtzd(d) => Some(*d),
+                        _ => None,
+                    },
+                    children: vec![],
+                },
+            };
+            children.push((loc.clone(), node));
+        }
+        path.pop();
+        Ok(ResultNode {
+            ob: bsk.ob,
+            data: match bsk.kids.get(&Loc::Phi) {
+                Some(Kid::Dtzd(d)) => Some(*d),
+                _ => None,
+            },
+            children,
+        })
+    }
+}
@@ -19,17 +19,26 @@
 // SOFTWARE.
 
 #[cfg(test)]
-use crate::emu::{Emu, Opt};
+use crate::emu::{Emu, Opt, Outcome, Termination, Warning, MAX_BASKETS, MAX_CYCLES, MAX_OBJECTS};
 
 #[cfg(test)]
-use crate::perf::Transition;
+use crate::perf::{Perf, Transition};
 
 #[cfg(test)]
 use crate::loc::Loc;
 
+#[cfg(test)]
+use std::path::Path;
+
 #[cfg(test)]
 use crate::locator::Locator;
 
+#[cfg(test)]
+use crate::atom;
+
+#[cfg(test)]
+use crate::basket::{Basket, Bk, Kid};
+
 #[cfg(test)]
 use crate::data::Data;
 
@@ -40,11 +49,26 @@ use crate::ph;
 use crate::assert_dataized_eq;
 
 #[cfg(test)]
-use crate::object::Object;
+use crate::object::{Ob, Object};
+
+#[cfg(test)]
+use std::cell::RefCell;
+
+#[cfg(test)]
+use std::rc::Rc;
+
+#[cfg(test)]
+use std::io::Write;
+
+#[cfg(test)]
+use crate::builder::ProgramBuilder;
 
 #[cfg(test)]
 use std::str::FromStr;
 
+#[cfg(test)]
+use serde_json::json;
+
 #[test]
 pub fn simple_dataization_cycle() {
     let mut emu = Emu::empty();
@@ -93,6 +117,16 @@ pub fn summarizes_two_numbers() {
     );
 }
 
+#[test]
+pub fn builds_summarizing_program() {
+    let mut b = ProgramBuilder::new();
+    let x = b.data(42);
+    let sum = b.atom("int-add", x, &[x]).unwrap();
+    let dec = b.decorator(sum);
+    let mut emu = b.build(dec).unwrap();
+    assert_eq!(84, emu.dataize().0);
+}
+
 // []
 //   int-add > @    v1
 //     int-add      v2
@@ -477,3 +511,1515 @@ pub fn recursive_fibonacci() {
         "Too many atomic operations"
     );
 }
+
+#[test]
+pub fn recursive_fibonacci_with_decrements() {
+    let input = 7;
+    let mut emu = Emu::from_str(
+        format!(
+            "
+            ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+            ν1(𝜋) ↦ ⟦ Δ ↦ 0x{:04X} ⟧
+            ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+            ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν13(𝜋) ⟧
+            ν6(𝜋) ↦ ⟦ λ ↦ int-dec, ρ ↦ ν8(𝜋) ⟧
+            ν7(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+            ν8(𝜋) ↦ ⟦ λ ↦ int-dec, ρ ↦ 𝜋.𝜋.𝛼0 ⟧
+            ν9(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν8(𝜋) ⟧
+            ν10(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν6(𝜋) ⟧
+            ν11(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν9(𝜋), 𝛼0 ↦ ν10(𝜋) ⟧
+            ν12(𝜋) ↦ ⟦ λ ↦ int-less, ρ ↦ ν7(𝜋), 𝛼0 ↦ 𝜋.𝛼0 ⟧
+            ν13(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν12(𝜋), 𝛼0 ↦ ν11(𝜋), 𝛼1 ↦ ν7(𝜋) ⟧
+            ",
+            input
+        )
+        .as_str(),
+    )
+    .unwrap();
+    assert_eq!(12, emu.objects().count());
+    let dtz = emu.dataize();
+    assert_eq!(fibo(input), dtz.0, "Wrong number calculated");
+}
+
+#[test]
+pub fn finds_difference_in_delta() {
+    let program = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x{} ⟧
+        ";
+    let first = Emu::from_str(&program.replace("{}", "002A")).unwrap();
+    let second = Emu::from_str(&program.replace("{}", "002B")).unwrap();
+    let diffs = first.diff(&second);
+    assert_eq!(1, diffs.len());
+    assert!(diffs[0].starts_with("ν1 "), "{}", diffs[0]);
+    assert!(first.diff(&first).is_empty());
+}
+
+#[test]
+pub fn releases_references_when_done() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::DontDelete);
+    assert_eq!(84, emu.dataize().0);
+    for bk in 0..emu.baskets.len() {
+        assert_eq!(0, emu.refs(bk as Bk), "β{} is still referenced", bk);
+    }
+}
+
+#[test]
+pub fn iterates_over_objects() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(
+        vec![0, 1, 2, 3],
+        emu.objects().map(|(ob, _)| ob).collect::<Vec<Ob>>()
+    );
+}
+
+#[test]
+pub fn allocates_objects_in_order() {
+    let mut emu = Emu::empty();
+    emu.put(1, Object::dataic(42));
+    assert_eq!(
+        0,
+        emu.put_next(Object::open().with(Loc::Phi, ph!("ν1"), false))
+    );
+    assert_eq!(2, emu.put_next(Object::dataic(7)));
+    assert_eq!(3, emu.put_next(Object::dataic(8)));
+    assert_eq!(4, emu.alloc_object());
+}
+
+#[test]
+pub fn rejects_empty_program() {
+    assert_eq!("Program is empty", Emu::from_str("").err().unwrap());
+    assert_eq!(
+        "Program is empty",
+        Emu::from_str("  \n\t  \n").err().unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "There is no root object ν0 to dataize")]
+pub fn fails_to_dataize_without_objects() {
+    Emu::empty().dataize();
+}
+
+#[test]
+pub fn reports_dataization_without_objects() {
+    let mut emu = Emu::empty();
+    assert_eq!(
+        "There is no root object ν0 to dataize",
+        emu.try_dataize().err().unwrap()
+    );
+    assert!(matches!(emu.evaluate(), Outcome::Stuck(_)));
+}
+
+#[test]
+pub fn reports_transitions_to_listener() {
+    let mut emu = Emu::empty();
+    emu.put(0, Object::open().with(Loc::Phi, ph!("ν1"), true));
+    emu.put(1, Object::dataic(42));
+    let seen = Rc::new(RefCell::new(vec![]));
+    let log = seen.clone();
+    emu.on_transition(move |t, bk| log.borrow_mut().push((t, bk)));
+    assert_eq!(42, emu.dataize().0);
+    assert_eq!(
+        vec![
+            (Transition::FND, 0),
+            (Transition::NEW, 0),
+            (Transition::CPY, 1),
+            (Transition::PPG, 1)
+        ],
+        *seen.borrow()
+    );
+}
+
+#[test]
+pub fn evaluates_to_value() {
+    let mut emu = Emu::empty();
+    emu.put(0, Object::open().with(Loc::Phi, ph!("ν1"), true));
+    emu.put(1, Object::dataic(42));
+    assert_eq!(Outcome::Value(42), emu.evaluate());
+}
+
+#[test]
+pub fn evaluates_to_stuck() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    assert!(matches!(emu.evaluate(), Outcome::Stuck(msg) if msg.starts_with("We are stuck")));
+}
+
+#[test]
+pub fn evaluates_to_too_many_cycles() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν13(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν6(𝜋) ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν5(𝜋) ⟧
+        ν7(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ν8(𝜋) ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν7(𝜋) ⟧
+        ν9(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν8(𝜋) ⟧
+        ν10(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν6(𝜋) ⟧
+        ν11(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν9(𝜋), 𝛼0 ↦ ν10(𝜋) ⟧
+        ν12(𝜋) ↦ ⟦ λ ↦ int-less, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ ν5(𝜋) ⟧
+        ν13(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν12(𝜋), 𝛼0 ↦ ν7(𝜋), 𝛼1 ↦ ν11(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.set_max_cycles(4);
+    assert_eq!(Outcome::TooManyCycles(5), emu.evaluate());
+}
+
+#[test]
+pub fn prints_labels_of_objects() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧ # foo
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧ # x
+        ",
+    )
+    .unwrap();
+    assert_eq!(Some("x".to_string()), emu.object(1).label);
+    assert!(emu.to_string().contains("ν1 ⟦! Δ↦0x002A⟧ # x"), "{}", emu);
+}
+
+#[test]
+pub fn rejects_garbage_after_object() {
+    let err = Emu::from_str("ν0(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧ foo").err().unwrap();
+    assert!(err.starts_with("Can't parse the line"), "{}", err);
+    assert!(Emu::from_str("ν0(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧ # foo").is_ok());
+}
+
+#[test]
+pub fn finds_self_referential_objects() {
+    let mut emu = Emu::empty();
+    emu.put(0, Object::open().with(Loc::Phi, ph!("ν1"), false));
+    emu.put(1, Object::open().with(Loc::Attr(0), ph!("ν3"), false));
+    emu.put(2, Object::open().with(Loc::Phi, ph!("ν2"), false));
+    emu.put(3, Object::dataic(42));
+    assert_eq!(vec![2], emu.self_referential_objects());
+}
+
+#[test]
+pub fn rejects_self_referential_program() {
+    let err = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ",
+    )
+    .err()
+    .unwrap();
+    assert_eq!("Object ν2 refers to itself", err);
+}
+
+#[test]
+pub fn grows_pool_of_baskets() {
+    let mut emu = Emu::with_basket_growth(16, 512);
+    emu.load(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν9(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν3(𝜋), 𝛼0 ↦ ν5(𝜋), 𝛼1 ↦ ν6(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ int-less, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ ν4(𝜋) ⟧
+        ν4(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+        ν5(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν6(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ), 𝛼0 ↦ ν7(𝜋) ⟧
+        ν7(𝜋) ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν8(𝜋) ⟧
+        ν8(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ν9(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ), 𝛼0 ↦ ν10(𝜋) ⟧
+        ν10(𝜋) ↦ ⟦ Δ ↦ 0x0028 ⟧
+        ",
+    )
+    .unwrap();
+    let (d, perf) = emu.dataize();
+    assert_eq!(42, d);
+    assert!(perf.peak > 128, "The peak is only {}", perf.peak);
+    assert_eq!(256, emu.baskets.len());
+}
+
+#[test]
+pub fn finds_critical_path() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν10(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν6(ξ), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν7(ξ), 𝛼0 ↦ ν4(𝜋) ⟧
+        ν4(𝜋) ↦ ⟦ 𝜑 ↦ ν8(ξ), 𝛼0 ↦ ν5(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ 𝜑 ↦ ν9(ξ), 𝛼0 ↦ 𝜋.𝜋.𝜋.𝜋.𝛼0 ⟧
+        ν6(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0 ⟧
+        ν7(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0 ⟧
+        ν8(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0 ⟧
+        ν9(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0 ⟧
+        ν10(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ), 𝛼0 ↦ ν11(𝜋) ⟧
+        ν11(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    // ν0 → ν10 → ν1 → ν2 → ν3 → ν4 → ν5 → ν9, through a, b, c, d and e
+    assert_eq!(Ok(7), emu.critical_path());
+}
+
+#[test]
+pub fn finds_no_critical_path_in_cycle() {
+    let mut emu = Emu::empty();
+    emu.put(0, Object::open().with(Loc::Phi, ph!("ν1"), false));
+    emu.put(1, Object::open().with(Loc::Phi, ph!("ν2"), false));
+    emu.put(2, Object::open().with(Loc::Attr(0), ph!("ν1"), false));
+    assert_eq!(
+        Err("There is a cycle: ν0 → ν1 → ν2 → ν1".to_string()),
+        emu.critical_path()
+    );
+}
+
+#[test]
+pub fn warns_about_wrongly_shared_constant() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦! λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼0 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν4(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+        ν4(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::VerifySharing);
+    assert_eq!(168, emu.dataize().0);
+    assert!(
+        emu.warnings()
+            .iter()
+            .any(|w| matches!(w, Warning::ImpureConstant { ob: 2, .. })),
+        "{:?}",
+        emu.warnings()
+    );
+    assert!(emu
+        .warnings()
+        .iter()
+        .map(|w| w.to_string())
+        .any(|w| w.starts_with("Constant ν2 shared at") && w.contains("depends on 𝜋")));
+}
+
+#[test]
+pub fn lists_pending_kids() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    let mut perf = Perf::new();
+    emu.step(&mut perf);
+    assert_eq!(vec![(2, vec![Loc::Phi])], emu.pending());
+    while emu.basket(0).kids.get(&Loc::Phi).unwrap().to_string() != "⇶0x0054" {
+        emu.step(&mut perf);
+    }
+    assert!(emu.pending().is_empty(), "{}", emu);
+}
+
+#[cfg(test)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+#[cfg(test)]
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+pub fn writes_event_log() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ) ⟧
+        ",
+    )
+    .unwrap();
+    let buf = Rc::new(RefCell::new(vec![]));
+    emu.set_event_writer(Shared(buf.clone()));
+    emu.opt(Opt::EventLog);
+    assert_eq!(84, emu.dataize().0);
+    let log = String::from_utf8(buf.borrow().clone()).unwrap();
+    assert!(log.contains("0,NEW,0,ν0\n"), "{}", log);
+    assert!(log.contains(",CPY,3,ν1\n"), "{}", log);
+    assert!(log.contains(",DLG,2,ν2\n"), "{}", log);
+}
+
+#[test]
+pub fn builds_result_tree() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::DontDelete);
+    assert_eq!(84, emu.dataize().0);
+    let root = emu.result_tree().unwrap();
+    assert_eq!(Some(84), root.data);
+    let (loc, sum) = &root.children[0];
+    assert_eq!(Loc::Phi, *loc);
+    assert_eq!(3, sum.ob);
+    assert_eq!(Some(84), sum.data);
+    let operands: Vec<(String, Ob, Option<Data>)> = sum
+        .children
+        .iter()
+        .map(|(loc, node)| (loc.to_string(), node.ob, node.data))
+        .collect();
+    assert_eq!(
+        vec![
+            ("𝛼0".to_string(), 1, Some(42)),
+            ("𝛼1".to_string(), 1, Some(42)),
+            ("𝜑".to_string(), 2, Some(84)),
+        ],
+        operands
+    );
+}
+
+#[test]
+pub fn dataizes_within_bounds() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν13(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν6(𝜋) ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν5(𝜋) ⟧
+        ν7(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ν8(𝜋) ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν7(𝜋) ⟧
+        ν9(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν8(𝜋) ⟧
+        ν10(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν6(𝜋) ⟧
+        ν11(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν9(𝜋), 𝛼0 ↦ ν10(𝜋) ⟧
+        ν12(𝜋) ↦ ⟦ λ ↦ int-less, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ ν5(𝜋) ⟧
+        ν13(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν12(𝜋), 𝛼0 ↦ ν7(𝜋), 𝛼1 ↦ ν11(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    let (outcome, perf) = emu.dataize_bounded(4);
+    assert_eq!(Outcome::TooManyCycles(4), outcome);
+    assert_eq!(4, perf.cycles);
+    assert!(perf.total_hits() > 0);
+    assert!(perf.total_ticks() > 0);
+    let (outcome, _) = emu.dataize_bounded(1000);
+    assert_eq!(Outcome::Value(21), outcome);
+}
+
+#[test]
+pub fn stops_bounded_dataization_on_read_budget() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν3(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ",
+    )
+    .unwrap();
+    emu.set_max_reads(3);
+    let (outcome, perf) = emu.dataize_bounded(1000);
+    assert_eq!(Outcome::TooManyReads { reads: 4, max: 3 }, outcome);
+    assert!(perf.cycles < 1000);
+    assert_eq!(None, emu.halted);
+}
+
+#[test]
+pub fn finds_unreachable_objects() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ) ⟧
+        ν6(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(vec![5, 6], emu.unreachable_objects());
+}
+
+#[test]
+pub fn memoizes_constants_between_runs() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦! λ ↦ int-add, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν1(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::MemoizeConstants);
+    let (d, perf) = emu.dataize();
+    assert_eq!(84, d);
+    assert_eq!(1, perf.total_atoms());
+    emu.objects[0] = Object::open().with(Loc::Phi, ph!("ν3"), false);
+    emu.reset();
+    let (d, perf) = emu.dataize();
+    assert_eq!(126, d);
+    assert_eq!(1, perf.total_atoms());
+    emu.clear_memo();
+    emu.reset();
+    let (d, perf) = emu.dataize();
+    assert_eq!(126, d);
+    assert_eq!(2, perf.total_atoms());
+}
+
+#[test]
+pub fn reports_all_syntax_errors() {
+    let errors = Emu::check_all(
+        "ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0xZZ ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+
+        ν3 ↦ ⟦ 𝜑 ↦ ν2(ξ) ⟧
+        ",
+    );
+    assert_eq!(
+        vec![2, 5],
+        errors.iter().map(|(line, _)| *line).collect::<Vec<usize>>()
+    );
+    assert!(errors[0].1.contains("Can't parse hex 'ZZ'"), "{:?}", errors);
+    assert!(
+        errors[1].1.starts_with("Can't parse the line"),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+pub fn finds_no_errors_in_correct_program() {
+    assert!(Emu::check_all("ν0(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧").is_empty());
+}
+
+#[test]
+pub fn rejects_broken_line_without_panic() {
+    let err = Emu::from_str("ν0 ↦ ⟦ Δ ↦ 0x002A ⟧").err().unwrap();
+    assert!(err.starts_with("Can't parse the line"), "{}", err);
+}
+
+#[test]
+pub fn builds_emu_with_objects() {
+    let mut emu = Emu::with_objects(vec![
+        (0, Object::open().with(Loc::Phi, ph!("ν3"), false)),
+        (1, Object::dataic(42)),
+        (
+            2,
+            Object::atomic("int-add".to_string(), atom::int_add)
+                .with(Loc::Rho, ph!("𝜋.𝛼0"), false)
+                .with(Loc::Attr(0), ph!("𝜋.𝛼1"), false),
+        ),
+        (
+            3,
+            Object::open()
+                .with(Loc::Phi, ph!("ν2"), true)
+                .with(Loc::Attr(0), ph!("ν1"), false)
+                .with(Loc::Attr(1), ph!("ν1"), false),
+        ),
+    ])
+    .unwrap();
+    assert_eq!(84, emu.dataize().0);
+}
+
+#[test]
+pub fn rejects_duplicate_objects() {
+    let err = Emu::with_objects(vec![(1, Object::dataic(42)), (1, Object::dataic(7))])
+        .err()
+        .unwrap();
+    assert_eq!("Object ν1 is given twice", err);
+    let err = Emu::with_objects(vec![(99, Object::dataic(42))])
+        .err()
+        .unwrap();
+    assert!(err.starts_with("Object ν99 is out of capacity"), "{}", err);
+}
+
+#[test]
+pub fn measures_time_of_atoms() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::TimeTransitions);
+    let (d, perf) = emu.dataize();
+    assert_eq!(84, d);
+    assert!(perf.atom_time.contains_key("int-add"), "{}", perf);
+}
+
+#[test]
+pub fn dataizes_all_objects() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν4(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0003 ⟧
+        ν4(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν1(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(
+        vec![(0, Ok(10)), (2, Ok(2)), (3, Ok(3)), (5, Ok(5))],
+        emu.dataize_all()
+    );
+}
+
+#[test]
+pub fn reports_deleted_atom_operand() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.baskets[0] = Basket::empty();
+    emu.inject(0, Basket::from_str("[ν0, ξ:β0, 𝜑⇉β2.𝜑]").unwrap());
+    emu.inject(
+        2,
+        Basket::from_str("[ν2, ξ:β0, ρ⇉β3.𝜑, 𝛼0⇉β3.𝜑, 𝜑→?]").unwrap(),
+    );
+    assert!(matches!(
+        emu.evaluate(),
+        Outcome::Stuck(msg) if msg.starts_with("We are stuck, atom operand was deleted: β2.")
+    ));
+}
+
+#[cfg(test)]
+use crate::resolver::{Found, Resolver, Standard};
+
+#[cfg(test)]
+struct Feeding;
+
+#[cfg(test)]
+impl Resolver for Feeding {
+    /// Gives the atom ν3 the live basket of ν2 as its \psi, even though
+    /// nobody waits for that basket anymore.
+    fn search(&self, emu: &Emu, bk: Bk, locator: &Locator) -> Result<Found, String> {
+        let (ob, psi, attr) = Standard.search(emu, bk, locator)?;
+        let feeder = emu.live_baskets().find(|(_, b)| b.ob == 2).map(|(b, _)| b);
+        match feeder {
+            Some(f) if ob == 3 => Ok((ob, f, attr)),
+            _ => Ok((ob, psi, attr)),
+        }
+    }
+}
+
+#[test]
+pub fn keeps_basket_feeding_unfinished_atom() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν2(ξ), 𝛼0 ↦ ν3(𝜋), 𝛼1 ↦ ν5(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν5(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν6(𝜋), 𝛼0 ↦ 𝜋.𝜑 ⟧
+        ν5(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν6(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν7(𝜋), 𝛼0 ↦ ν7(𝜋) ⟧
+        ν7(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν5(𝜋), 𝛼0 ↦ ν5(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::PrioritizeTouched);
+    emu.set_resolver(Feeding);
+    assert_eq!(35, emu.dataize().0);
+}
+
+#[test]
+pub fn reports_its_limits() {
+    let emu = Emu::empty();
+    assert_eq!(MAX_OBJECTS, emu.max_objects());
+    assert_eq!(MAX_BASKETS, emu.max_baskets());
+    assert_eq!(MAX_CYCLES, emu.max_cycles());
+    let mut emu = Emu::with_capacity(32, 64);
+    emu.set_max_cycles(100);
+    assert_eq!(32, emu.max_objects());
+    assert_eq!(64, emu.max_baskets());
+    assert_eq!(100, emu.max_cycles());
+    assert_eq!(512, Emu::with_basket_growth(16, 512).max_baskets());
+}
+
+#[test]
+pub fn hashes_content_of_program() {
+    let mut first = Emu::empty();
+    first.put(0, Object::open().with(Loc::Phi, ph!("ν1"), false));
+    first.put(
+        1,
+        Object::open()
+            .with(Loc::Attr(0), ph!("ν2"), false)
+            .with(Loc::Phi, ph!("𝜋.𝛼0"), false),
+    );
+    first.put(2, Object::dataic(42));
+    let mut second = Emu::empty();
+    second.put(2, Object::dataic(42).with_label("answer"));
+    second.put(
+        1,
+        Object::open()
+            .with(Loc::Phi, ph!("𝜋.𝛼0"), false)
+            .with(Loc::Attr(0), ph!("ν2"), false),
+    );
+    second.put(0, Object::open().with(Loc::Phi, ph!("ν1"), false));
+    assert_eq!(first.content_hash(), second.content_hash());
+    let mut third = Emu::empty();
+    third.put(0, Object::open().with(Loc::Phi, ph!("ν1"), false));
+    third.put(
+        1,
+        Object::open()
+            .with(Loc::Attr(0), ph!("ν2"), false)
+            .with(Loc::Phi, ph!("𝜋.𝛼0"), false),
+    );
+    third.put(2, Object::dataic(43));
+    assert_ne!(first.content_hash(), third.content_hash());
+}
+
+#[test]
+pub fn prioritizes_touched_kids() {
+    let program = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν3(𝜋), 𝛼1 ↦ ν4(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν4(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+    ";
+    let mut slow = Emu::from_str(program).unwrap();
+    let (d, before) = slow.dataize();
+    assert_eq!(42, d);
+    let mut fast = Emu::from_str(program).unwrap();
+    fast.opt(Opt::PrioritizeTouched);
+    let (d, after) = fast.dataize();
+    assert_eq!(42, d);
+    assert!(
+        after.cycles < before.cycles,
+        "{} vs {}",
+        after.cycles,
+        before.cycles
+    );
+}
+
+#[test]
+pub fn works_with_sparse_program() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν5(𝜋) ⟧
+        ν9(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν5(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν9(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν12(𝜋) ↦ ⟦ 𝜑 ↦ ν14(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(vec![12], emu.unreachable_objects());
+    assert_eq!(vec![(12, Loc::Phi, 14)], emu.dangling_references());
+    assert!(emu.self_referential_objects().is_empty());
+    assert_eq!(Ok(2), emu.critical_path());
+    assert_eq!(49, emu.dataize().0);
+}
+
+#[test]
+pub fn returns_result_as_bytes() {
+    let mut emu = Emu::from_str("ν0(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧").unwrap();
+    assert_eq!(Ok(vec![0x00, 0x2A]), emu.result_bytes());
+}
+
+#[test]
+pub fn finds_atoms_without_operands() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν1(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(
+        vec![(
+            2,
+            "The atom int-add needs 𝛼0, but ν2 doesn't have it".to_string()
+        )],
+        emu.lint_atoms()
+    );
+}
+
+#[test]
+pub fn serializes_perf_report() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    let (d, perf) = emu.dataize();
+    assert_eq!(84, d);
+    assert_eq!(
+        json!({
+            "atoms": {"int-add": 1},
+            "cycles": 5,
+            "fanouts": {"1": 3},
+            "hits": {"CPY": 1, "DLG": 3, "FND": 3, "NEW": 3, "PPG": 3},
+            "overflows": 0,
+            "peak": 3,
+            "reads": 5,
+            "ticks": {"CPY": 12, "DEL": 4, "DLG": 12, "FND": 24, "NEW": 24, "PPG": 59}
+        }),
+        serde_json::to_value(perf.to_report()).unwrap()
+    );
+}
+
+#[test]
+pub fn dataizes_twice_without_reset() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::StopWhenStuck);
+    let (first, before) = emu.dataize();
+    let (second, after) = emu.dataize();
+    assert_eq!(84, first);
+    assert_eq!(first, second);
+    assert_eq!(before.to_report(), after.to_report());
+    emu.reset();
+    let (third, again) = emu.dataize();
+    assert_eq!(84, third);
+    assert_eq!(before.to_report(), again.to_report());
+}
+
+#[cfg(test)]
+fn int_add_delta(emu: &mut Emu, bk: Bk) -> Option<Data> {
+    let rho = emu
+        .read_delta(bk, Loc::Rho)
+        .or_else(|| emu.read(bk, Loc::Rho))?;
+    let a0 = emu
+        .read_delta(bk, Loc::Attr(0))
+        .or_else(|| emu.read(bk, Loc::Attr(0)))?;
+    Some(rho + a0)
+}
+
+#[test]
+pub fn reads_delta_without_copying() {
+    let txt = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+    ";
+    let mut emu = Emu::from_str(txt).unwrap();
+    let (slow, before) = emu.dataize();
+    let mut emu = Emu::from_str(txt).unwrap();
+    let mut obj = Object::atomic("int-add-delta".to_string(), int_add_delta);
+    obj.attrs = emu.objects[1].attrs.clone();
+    emu.objects[1] = obj;
+    assert_eq!(None, emu.read_delta(0, Loc::Phi));
+    let (fast, after) = emu.dataize();
+    assert_eq!(49, slow);
+    assert_eq!(slow, fast);
+    assert!(
+        after.hits.get(&Transition::CPY).unwrap_or(&0)
+            < before.hits.get(&Transition::CPY).unwrap_or(&0)
+    );
+    assert!(after.cycles < before.cycles);
+}
+
+#[test]
+pub fn leaves_unresolved_attribute_in_lenient_mode() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(ξ) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼7 ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::DontDelete);
+    emu.opt(Opt::LenientResolution);
+    assert!(matches!(emu.evaluate(), Outcome::Stuck(_)));
+    let (bk, bsk) = emu.live_baskets().find(|(_, bsk)| bsk.ob == 1).unwrap();
+    assert!(matches!(bsk.kids.get(&Loc::Rho), Some(Kid::Dtzd(42))));
+    assert_eq!(1, emu.unresolved().len());
+    let (ubk, loc, err) = &emu.unresolved()[0];
+    assert_ne!(bk, *ubk);
+    assert_eq!(Loc::Phi, *loc);
+    assert!(err.contains("Can't find 𝛼7"), "{}", err);
+}
+
+#[test]
+pub fn compacts_sparse_baskets() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::DontDelete);
+    emu.baskets[0] = Basket::empty();
+    emu.inject(0, Basket::from_str("[ν0, ξ:β0, 𝜑⇉β7.𝜑]").unwrap());
+    emu.inject(
+        7,
+        Basket::from_str("[ν2, ξ:β0, ρ⇉β42.𝜑, 𝛼0→(ν1;β7), 𝜑→?]").unwrap(),
+    );
+    emu.inject(42, Basket::from_str("[ν1, ξ:β7, 𝜑→?]").unwrap());
+    emu.compact();
+    assert_eq!(
+        vec![0, 1, 2],
+        emu.live_baskets().map(|(bk, _)| bk).collect::<Vec<Bk>>()
+    );
+    assert_eq!("[ν0, ξ:β0, 𝜑⇉β1.𝜑]", emu.basket(0).to_string());
+    assert_eq!(
+        "[ν2, ξ:β0, ρ⇉β2.𝜑, 𝛼0→(ν1;β1), 𝜑→?]",
+        emu.basket(1).to_string()
+    );
+    assert_eq!("[ν1, ξ:β1, 𝜑→?]", emu.basket(2).to_string());
+    assert_eq!(1, emu.refs(1));
+    assert_eq!(1, emu.refs(2));
+    assert_eq!(MAX_BASKETS, emu.baskets.len());
+    assert_eq!(84, emu.dataize().0);
+}
+
+#[test]
+pub fn expects_to_get_stuck() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν3(𝜋) ↦ ⟦ 𝛼0 ↦ ν2(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    let report = emu.expect_stuck().unwrap();
+    assert!(
+        report.message.starts_with("We are stuck"),
+        "{}",
+        report.message
+    );
+    let (bk, _) = emu.live_baskets().find(|(_, bsk)| bsk.ob == 3).unwrap();
+    assert!(
+        report.blocked.contains(&(bk, Loc::Phi)),
+        "{:?}",
+        report.blocked
+    );
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(
+        "Expected to get stuck, but dataized to 0x002A",
+        emu.expect_stuck().unwrap_err()
+    );
+}
+
+#[test]
+pub fn loads_unknown_lambda_leniently() {
+    let program = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ string-concat, ρ ↦ ν2(𝜋) ⟧
+    ";
+    assert!(Emu::from_str(program).is_err());
+    let mut emu = Emu::empty();
+    emu.load_lenient(program).unwrap();
+    let expected = vec![Warning::UnknownLambda {
+        ob: 3,
+        name: "string-concat".to_string(),
+    }];
+    assert_eq!(expected, emu.warnings());
+    assert!(emu.warnings()[0]
+        .to_string()
+        .starts_with("Unknown lambda 'string-concat'"));
+    assert_eq!(84, emu.dataize().0);
+    assert_eq!(expected, emu.warnings());
+    emu.reset();
+    assert_eq!(expected, emu.warnings());
+    let mut emu = Emu::empty();
+    emu.load_lenient(&program.replace("𝜑 ↦ ν1(𝜋)", "𝜑 ↦ ν3(𝜋)"))
+        .unwrap();
+    emu.set_max_cycles(100);
+    assert_eq!(Outcome::TooManyCycles(101), emu.evaluate());
+}
+
+#[test]
+pub fn lists_object_graph() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1, 𝛼1 ↦ ν1 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            (0, Loc::Phi, 3),
+            (3, Loc::Attr(0), 1),
+            (3, Loc::Attr(1), 1),
+            (3, Loc::Phi, 2)
+        ],
+        emu.object_graph()
+    );
+}
+
+#[test]
+pub fn loads_program_with_bom_and_crlf() {
+    let program = "\u{feff}ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧\r\n\
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧\r\n\
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧\r\n";
+    assert!(Emu::check_all(program).is_empty());
+    assert_eq!(84, Emu::from_str(program).unwrap().dataize().0);
+}
+
+#[test]
+pub fn clones_program_without_baskets() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::DontDelete);
+    emu.set_max_cycles(50);
+    let mut copy = emu.clone_program();
+    assert_eq!(84, copy.dataize().0);
+    assert!(copy.live_baskets().count() > 1);
+    assert_eq!(1, emu.live_baskets().count());
+    assert!(copy.opts.contains(&Opt::DontDelete));
+    assert_eq!(50, copy.max_cycles());
+    assert_eq!(1, emu.clone_program().live_baskets().count());
+    assert_eq!(84, emu.dataize().0);
+}
+
+#[test]
+pub fn collects_warnings_in_order() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν4(ξ) ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-inc, ρ ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x7FFF ⟧
+        ν4(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼7 ⟧
+        ",
+    )
+    .unwrap();
+    emu.opt(Opt::LenientResolution);
+    assert!(matches!(emu.evaluate(), Outcome::Stuck(_)));
+    let warnings = emu.warnings();
+    assert_eq!(2, warnings.len(), "{:?}", warnings);
+    assert!(matches!(warnings[0], Warning::Overflow { .. }));
+    assert!(matches!(
+        &warnings[1],
+        Warning::UnresolvedAttr { locator, .. } if locator.to_string() == "𝜋.𝛼7"
+    ));
+    emu.reset();
+    assert!(emu.warnings().is_empty());
+}
+
+#[test]
+pub fn dataizes_same_way_with_and_without_snapshots() {
+    let program = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+    ";
+    let mut lean = Emu::from_str(program).unwrap();
+    let mut logged = Emu::from_str(program).unwrap();
+    logged.opt(Opt::LogSnapshots);
+    logged.opt(Opt::StopWhenStuck);
+    let (d1, p1) = lean.dataize();
+    let (d2, p2) = logged.dataize();
+    assert_eq!(84, d1);
+    assert_eq!(d1, d2);
+    assert_eq!(p1.to_report(), p2.to_report());
+}
+
+#[test]
+pub fn runs_programs_with_shared_library() {
+    let lib = Emu::with_shared_library(
+        "
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(
+        Ok(84),
+        lib.run_with(
+            "
+            ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+            ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+            ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ), 𝛼0 ↦ ν2(𝜋), 𝛼1 ↦ ν2(𝜋) ⟧
+            "
+        )
+    );
+    assert_eq!(
+        Ok(10),
+        lib.run_with(
+            "
+            ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν4(𝜋) ⟧
+            ν2(𝜋) ↦ ⟦ Δ ↦ 0x0003 ⟧
+            ν3(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+            ν4(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ), 𝛼0 ↦ ν2(𝜋), 𝛼1 ↦ ν3(𝜋) ⟧
+            "
+        )
+    );
+    assert_eq!(
+        Err("Object ν1 is given twice".to_string()),
+        lib.run_with("ν1(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧")
+    );
+    assert_eq!(1, lib.objects().count());
+}
+
+#[test]
+pub fn stops_on_too_deep_xi_chain() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ) ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ) ⟧
+        ",
+    )
+    .unwrap();
+    emu.set_max_psi_depth(8);
+    assert_eq!(Outcome::TooDeepXi { depth: 9, max: 8 }, emu.evaluate());
+    emu.reset();
+    assert_eq!(
+        "The ξ chain is too deep: 9 baskets, while only 8 allowed",
+        emu.try_dataize().err().unwrap()
+    );
+}
+
+#[test]
+pub fn passes_xi_within_depth_limit() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν6(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν4(ξ), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν5(ξ), 𝛼0 ↦ 𝜋.𝜋.𝛼0 ⟧
+        ν4(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0 ⟧
+        ν5(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0 ⟧
+        ν6(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ), 𝛼0 ↦ ν7(𝜋) ⟧
+        ν7(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    emu.set_max_psi_depth(8);
+    assert_eq!(42, emu.dataize().0);
+}
+
+#[test]
+pub fn streams_dataized_values() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν4(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0003 ⟧
+        ν4(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    let seen = Rc::new(RefCell::new(vec![]));
+    let log = seen.clone();
+    emu.on_dataized(move |bk, loc, d| log.borrow_mut().push((bk, loc.clone(), d)));
+    assert_eq!(10, emu.dataize().0);
+    let values: Vec<Data> = seen.borrow().iter().map(|(_, _, d)| *d).collect();
+    let five = values.iter().position(|d| *d == 5).unwrap();
+    let ten = values.iter().position(|d| *d == 10).unwrap();
+    assert!(five < ten, "{:?}", values);
+    assert_eq!(
+        Some(&(0, Loc::Phi, 10)),
+        seen.borrow().last(),
+        "{:?}",
+        values
+    );
+}
+
+#[test]
+pub fn restores_from_snapshot() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0003 ⟧
+        ",
+    )
+    .unwrap();
+    let mut perf = Perf::new();
+    emu.step(&mut perf);
+    emu.step(&mut perf);
+    let mut restored = Emu::restore(&emu.snapshot()).unwrap();
+    assert_eq!(emu.snapshot(), restored.snapshot());
+    assert_eq!(5, restored.dataize().0);
+}
+
+#[test]
+pub fn restores_negative_data_from_snapshot() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0xFFFF ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0xFFF9 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(-8, emu.dataize().0);
+    let text = emu.snapshot();
+    assert!(text.contains("Δ↦0xFFFF"), "{}", text);
+    assert!(text.contains("𝜑⇶0xFFF8"), "{}", text);
+    let restored = Emu::restore(&text).unwrap();
+    assert_eq!(text, restored.snapshot());
+    assert_eq!(Some(-1), restored.object(2).delta);
+}
+
+#[test]
+pub fn rejects_broken_snapshot() {
+    let err = Emu::restore("ν0(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧\nβ9999 [ν0, ξ:β0]")
+        .err()
+        .unwrap();
+    assert!(err.contains("β9999 is out of capacity"), "{}", err);
+}
+
+#[test]
+pub fn stops_on_exceeded_read_budget() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν3(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ",
+    )
+    .unwrap();
+    emu.set_max_reads(3);
+    assert_eq!(Outcome::TooManyReads { reads: 4, max: 3 }, emu.evaluate());
+    emu.reset();
+    assert_eq!(
+        "The read budget exceeded: 4 reads, while only 3 allowed",
+        emu.try_dataize().err().unwrap()
+    );
+    emu.reset();
+    assert_eq!(
+        "The read budget exceeded: 4 reads, while only 3 allowed",
+        emu.result_bytes().err().unwrap()
+    );
+    assert_eq!(
+        Err("The read budget exceeded: 4 reads, while only 3 allowed".to_string()),
+        emu.dataize_all()[0].1
+    );
+}
+
+#[test]
+pub fn counts_reads_in_perf() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν3(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ",
+    )
+    .unwrap();
+    emu.set_max_reads(100);
+    let (d, perf) = emu.dataize();
+    assert_eq!(4, d);
+    assert!(perf.reads > 3, "{}", perf);
+    assert!(perf.to_string().contains("Reads: "), "{}", perf);
+}
+
+#[test]
+pub fn records_fanout_of_shared_value() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-sum, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋), 𝛼1 ↦ ν2(𝜋), 𝛼2 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦! λ ↦ int-add, ρ ↦ ν3(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0015 ⟧
+        ",
+    )
+    .unwrap();
+    let (d, perf) = emu.dataize();
+    assert_eq!(168, d);
+    assert_eq!(4, perf.max_fanout(), "{:?}", perf.fanouts);
+    assert_eq!(Some(&1), perf.fanouts.get(&4));
+    assert!(perf.to_string().contains("4 waiters: 1"), "{}", perf);
+}
+
+#[test]
+pub fn loads_imported_library() {
+    let mut emu = Emu::empty();
+    emu.load_file(Path::new("tests/resources/written_import_test"))
+        .unwrap();
+    assert_eq!(84, emu.dataize().0);
+}
+
+#[test]
+pub fn rejects_cyclic_imports() {
+    let err = Emu::empty()
+        .load_file(Path::new("tests/resources/written_cyclic_import_a"))
+        .err()
+        .unwrap();
+    assert!(err.contains("is cyclic"), "{}", err);
+}
+
+#[test]
+pub fn rejects_clashing_imports() {
+    let err = Emu::empty()
+        .load_file(Path::new("tests/resources/written_clash_test"))
+        .err()
+        .unwrap();
+    assert!(err.starts_with("Object ν1 is defined in both "), "{}", err);
+    assert!(err.contains("written_clash_lib_a and "), "{}", err);
+    assert!(err.ends_with("written_clash_lib_b"), "{}", err);
+}
+
+#[test]
+pub fn changes_objects_in_place() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(4, emu.dataize().0);
+    emu.object_mut(2).unwrap().delta = Some(21);
+    assert!(emu.object_mut(MAX_OBJECTS).is_none());
+    emu.reset();
+    assert_eq!(42, emu.dataize().0);
+    for (_, obj) in emu.objects_mut() {
+        if obj.delta.is_some() {
+            obj.delta = Some(1);
+        }
+    }
+    assert_eq!(3, emu.objects_mut().count());
+    emu.reset();
+    assert_eq!(2, emu.dataize().0);
+}
+
+#[test]
+pub fn folds_constants_before_dataization() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ) ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(1, emu.fold_constants());
+    assert_eq!(Some(84), emu.object(3).delta);
+    assert_eq!(0, emu.fold_constants());
+    assert!(emu
+        .dataize_all()
+        .into_iter()
+        .filter(|(ob, _)| *ob == 0 || *ob == 5)
+        .all(|(_, ret)| ret == Ok(84)));
+    assert_eq!(84, emu.dataize().0);
+}
+
+#[test]
+pub fn keeps_objects_whose_attributes_are_read() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν4(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ν4(𝜋) ↦ ⟦ 𝜑 ↦ ν6(ξ), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν6(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ 𝜋.𝛼0.𝛼1 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(0, emu.fold_constants());
+    assert!(emu.object(3).delta.is_none());
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν5(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν6(ξ) ⟧
+        ν6(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ 𝜋.𝜑.𝛼0 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(0, emu.fold_constants());
+    assert!(emu.object(3).delta.is_none());
+}
+
+#[test]
+pub fn folds_nested_constants_to_fixpoint() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-neg, ρ ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-times, ρ ↦ ν3(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0003 ⟧
+        ν4(𝜋) ↦ ⟦ λ ↦ int-div, ρ ↦ ν3(𝜋), 𝛼0 ↦ ν5(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(2, emu.fold_constants());
+    assert_eq!(Some(-9), emu.object(1).delta);
+    assert!(emu.object(4).lambda.is_some());
+    assert_eq!(-9, emu.dataize().0);
+}
+
+#[test]
+pub fn believes_recursion_over_counter_terminates() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0007 ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν13(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+        ν6(𝜋) ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν5(𝜋) ⟧
+        ν7(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+        ν8(𝜋) ↦ ⟦ λ ↦ int-sub, ρ ↦ 𝜋.𝜋.𝛼0, 𝛼0 ↦ ν7(𝜋) ⟧
+        ν9(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν8(𝜋) ⟧
+        ν10(𝜋) ↦ ⟦ 𝜑 ↦ ν3(ξ), 𝛼0 ↦ ν6(𝜋) ⟧
+        ν11(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν9(𝜋), 𝛼0 ↦ ν10(𝜋) ⟧
+        ν12(𝜋) ↦ ⟦ λ ↦ int-less, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ ν5(𝜋) ⟧
+        ν13(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν12(𝜋), 𝛼0 ↦ ν7(𝜋), 𝛼1 ↦ ν11(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(Termination::Terminates, emu.likely_terminates());
+}
+
+#[test]
+pub fn suspects_self_looping_program() {
+    let emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ) ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ) ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(Termination::MayNotTerminate, emu.likely_terminates());
+}
+
+#[test]
+pub fn classifies_other_programs() {
+    let acyclic = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν2(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(Termination::Terminates, acyclic.likely_terminates());
+    let increasing = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν1(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ λ ↦ bool-if, ρ ↦ ν2(𝜋), 𝛼0 ↦ ν3(𝜋), 𝛼1 ↦ ν4(𝜋) ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-less, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+        ν4(𝜋) ↦ ⟦ 𝜑 ↦ ν1(ξ), 𝛼0 ↦ ν5(𝜋) ⟧
+        ν5(𝜋) ↦ ⟦ λ ↦ int-inc, ρ ↦ 𝜋.𝜋.𝛼0 ⟧
+        ",
+    )
+    .unwrap();
+    assert_eq!(Termination::Unknown, increasing.likely_terminates());
+}
+
+#[test]
+pub fn compacts_after_deletion() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦! λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν4(𝜋), 𝛼0 ↦ ν4(𝜋) ⟧
+        ν4(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    let mut perf = Perf::new();
+    let mut orphaned = false;
+    while !matches!(emu.basket(0).kids.get(&Loc::Phi), Some(Kid::Dtzd(_))) {
+        emu.step(&mut perf);
+        orphaned |= emu
+            .live_baskets()
+            .any(|(_, bsk)| emu.basket(bsk.psi).is_empty());
+        emu.compact();
+        let live: Vec<Bk> = emu.live_baskets().map(|(bk, _)| bk).collect();
+        assert_eq!((0..live.len() as Bk).collect::<Vec<Bk>>(), live);
+    }
+    assert!(orphaned);
+    assert!(emu
+        .live_baskets()
+        .all(|(_, bsk)| !emu.basket(bsk.psi).is_empty()));
+    emu.reset();
+    emu.step(&mut perf);
+    emu.compact();
+    assert_eq!(168, emu.dataize().0);
+}
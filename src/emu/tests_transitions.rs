@@ -30,6 +30,12 @@ use crate::emu::Emu;
 #[cfg(test)]
 use crate::perf::Perf;
 
+#[cfg(test)]
+use crate::object::Object;
+
+#[cfg(test)]
+use crate::atom;
+
 #[test]
 pub fn deletes_one_basket() {
     let mut emu = Emu::empty();
@@ -39,3 +45,25 @@ pub fn deletes_one_basket() {
     emu.delete(&mut perf, bk);
     assert!(emu.basket(bk).is_empty())
 }
+
+#[test]
+pub fn keeps_waiting_basket() {
+    let mut emu = Emu::empty();
+    emu.inject(1, Basket::from_str("[ν1, ξ:β1, 𝜑⇶0x002A]").unwrap());
+    emu.inject(2, Basket::from_str("[ν2, ξ:β1, 𝜑⇉β1.𝜑]").unwrap());
+    assert_eq!(1, emu.refs(1));
+    let mut perf = Perf::new();
+    emu.delete(&mut perf, 1);
+    assert!(!emu.basket(1).is_empty())
+}
+
+#[test]
+pub fn keeps_basket_of_atom_operands() {
+    let mut emu = Emu::empty();
+    emu.put(2, Object::atomic("int-add".to_string(), atom::int_add));
+    emu.inject(1, Basket::from_str("[ν1, ξ:β0, 𝛼0⇶0x002A]").unwrap());
+    emu.inject(2, Basket::from_str("[ν2, ξ:β1, ρ→∅, 𝜑→?]").unwrap());
+    let mut perf = Perf::new();
+    emu.delete(&mut perf, 1);
+    assert!(!emu.basket(1).is_empty())
+}
@@ -19,19 +19,16 @@
 // SOFTWARE.
 
 use crate::basket::{Basket, Bk, Kid};
-use crate::emu::{Emu, MAX_BASKETS, ROOT_BK, ROOT_OB};
+use crate::emu::{Emu, Opt, Outcome, Warning, ROOT_BK};
 use crate::loc::Loc;
 use crate::locator::Locator;
 use crate::object::{Ob, Object};
 use crate::perf::{Perf, Transition};
+use crate::resolver::Found;
 use itertools::Itertools;
-use log::trace;
-
-macro_rules! join {
-    ($log:expr) => {
-        $log.iter().join("; ")
-    };
-}
+use log::{trace, warn};
+use std::io::Write;
+use std::time::Instant;
 
 impl Emu {
     /// Copy data from object to basket.
@@ -40,9 +37,9 @@ impl Emu {
         if let Some(Kid::Rqtd) = bsk.kids.get(&Loc::Phi) {
             let obj = self.object(bsk.ob);
             if let Some(d) = obj.delta {
-                let _ = &self.baskets[bk as usize].put(Loc::Phi, Kid::Dtzd(d));
+                self.put_kid(bk, Loc::Phi, Kid::Dtzd(d));
                 trace!("copy(β{}) -> 0x{:04X}", bk, d);
-                perf.hit(Transition::CPY);
+                self.hit(perf, Transition::CPY, bk);
             }
         }
         perf.tick(Transition::CPY);
@@ -67,9 +64,12 @@ impl Emu {
                 }
             }
         }
+        if !changes.is_empty() {
+            perf.fanout(changes.len());
+        }
         for (b, l, d) in changes.iter() {
-            let _ = &self.baskets[*b as usize].put(l.clone(), Kid::Dtzd(*d));
-            perf.hit(Transition::PPG);
+            self.put_kid(*b, l.clone(), Kid::Dtzd(*d));
+            self.hit(perf, Transition::PPG, bk);
         }
         perf.tick(Transition::PPG);
     }
@@ -84,33 +84,16 @@ impl Emu {
         if obj.constant {
             return;
         }
-        let mut ready = true;
-        for kid in bsk.kids.values() {
-            if !matches!(kid, Kid::Empt) && !matches!(kid, Kid::Dtzd(_)) {
-                ready = false;
-                break;
-            }
-            if matches!(kid, Kid::Dtzd(_)) {
-                for i in 0..self.baskets.len() {
-                    let wbsk = self.basket(i as Bk);
-                    if wbsk.is_empty() {
-                        continue;
-                    }
-                    perf.tick(Transition::DEL);
-                    for v in wbsk.kids.values() {
-                        if let Kid::Wait(b, _) = v {
-                            if *b == bk {
-                                ready = false
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let ready = self.refs(bk) == 0
+            && bsk
+                .kids
+                .values()
+                .all(|kid| matches!(kid, Kid::Empt | Kid::Dtzd(_)))
+            && self.feeders(bk) == 0;
         if ready {
+            self.hit(perf, Transition::DEL, bk);
             self.baskets[bk as usize] = Basket::empty();
             trace!("delete(β{})", bk);
-            perf.hit(Transition::DEL);
         }
         perf.tick(Transition::DEL);
     }
@@ -121,12 +104,18 @@ impl Emu {
         if let Some(Kid::Rqtd) = bsk.kids.get(&Loc::Phi) {
             if !bsk.kids.values().any(|k| matches!(&k, Kid::Wait(_, _))) {
                 let obj = self.object(bsk.ob);
-                if let Some((n, func)) = &obj.lambda {
-                    let name = n.clone();
-                    perf.hit(Transition::DLG);
-                    if let Some(d) = func(self, bk) {
+                if let Some((name, func)) = obj.lambda.clone() {
+                    self.hit(perf, Transition::DLG, bk);
+                    let start = Instant::now();
+                    let ret = func(self, bk);
+                    perf.overflows += std::mem::take(&mut self.overflows);
+                    perf.reads = self.reads;
+                    if self.opts.contains(&Opt::TimeTransitions) {
+                        perf.time(name.clone(), start.elapsed());
+                    }
+                    if let Some(d) = ret {
                         perf.atom(name);
-                        let _ = &self.baskets[bk as usize].put(Loc::Phi, Kid::Dtzd(d));
+                        self.put_kid(bk, Loc::Phi, Kid::Dtzd(d));
                         trace!("delegate(β{}) -> 0x{:04X}", bk, d);
                     }
                 }
@@ -141,24 +130,36 @@ impl Emu {
             let ob = self.basket(bk).ob;
             let obj = self.object(ob);
             if let Some((locator, advice)) = obj.attrs.get(&loc) {
-                let (tob, psi, attr) = self
-                    .search(bk, locator)
-                    .unwrap_or_else(|_| panic!("Can't find {} from β{}/ν{}", locator, bk, ob));
+                let (tob, psi, attr) = match self.search(bk, locator) {
+                    Ok(found) => found,
+                    Err(e) if self.opts.contains(&Opt::LenientResolution) => {
+                        if !self
+                            .unresolved
+                            .iter()
+                            .any(|(b, l, _)| *b == bk && *l == loc)
+                        {
+                            let locator = locator.clone();
+                            self.unresolved.push((bk, loc.clone(), e));
+                            self.warn(Warning::UnresolvedAttr { bk, locator });
+                        }
+                        perf.tick(Transition::FND);
+                        return;
+                    }
+                    Err(e) => panic!("Can't find {} from β{}/ν{}: {}", locator, bk, ob, e),
+                };
                 let tpsi = if *advice { bk } else { psi };
                 if let Some((pbk, ploc)) = attr {
                     let bsk = self.basket(pbk);
                     if let Some(Kid::Empt) = bsk.kids.get(&ploc) {
-                        let _ = &self.baskets[pbk as usize]
-                            .put(ploc.clone(), Kid::Wait(bk, loc.clone()));
-                        let _ = &self.baskets[bk as usize].put(loc.clone(), Kid::Need(tob, tpsi));
+                        self.put_kid(pbk, ploc.clone(), Kid::Wait(bk, loc.clone()));
+                        self.put_kid(bk, loc.clone(), Kid::Need(tob, tpsi));
                     } else {
-                        let _ = &self.baskets[bk as usize]
-                            .put(loc.clone(), Kid::Wait(pbk, ploc.clone()));
+                        self.put_kid(bk, loc.clone(), Kid::Wait(pbk, ploc.clone()));
                     }
                 } else {
-                    let _ = &self.baskets[bk as usize].put(loc.clone(), Kid::Need(tob, tpsi));
+                    self.put_kid(bk, loc.clone(), Kid::Need(tob, tpsi));
                 }
-                perf.hit(Transition::FND);
+                self.hit(perf, Transition::FND, bk);
             }
         }
         perf.tick(Transition::FND);
@@ -167,118 +168,82 @@ impl Emu {
     /// Make new basket for this attribute.
     #[allow(clippy::new_ret_no_self)]
     pub fn new(&mut self, perf: &mut Perf, bk: Bk, loc: Loc) {
-        if let Some(Kid::Need(tob, psi)) = self.basket(bk).kids.get(&loc) {
+        if let Some(&Kid::Need(tob, psi)) = self.basket(bk).kids.get(&loc) {
             let ob = self.basket(bk).ob;
-            let nbk = if let Some(ebk) = self.stashed(*tob, *psi) {
+            let nbk = if let Some(ebk) = self.stashed(tob, psi) {
                 trace!("new(β{}/ν{}, {}) -> link to stashed β{}", bk, ob, loc, ebk);
+                if self.opts.contains(&Opt::VerifySharing) {
+                    self.verify(ebk, psi);
+                }
                 ebk
             } else {
-                let id = self
-                    .baskets
-                    .iter()
-                    .find_position(|b| b.is_empty())
-                    .unwrap_or_else(|| {
-                        panic!("No more empty baskets left in the pool of {}", MAX_BASKETS)
-                    })
-                    .0 as Bk;
-                let mut bsk = Basket::start(*tob, *psi);
-                for k in self.object(*tob).attrs.keys() {
-                    bsk.put(k.clone(), Kid::Empt);
+                if let Some(max) = self.max_psi_depth {
+                    let depth = self.psi_depth(psi) + 1;
+                    if depth > max {
+                        trace!(
+                            "The ξ chain is too deep for ν{} at β{}.{}: {} baskets, while only {} allowed",
+                            tob, bk, loc, depth, max
+                        );
+                        self.halted.get_or_insert(Outcome::TooDeepXi { depth, max });
+                        return;
+                    }
+                }
+                let id = match self.baskets.iter().position(|b| b.is_empty()) {
+                    Some(pos) => pos as Bk,
+                    None => self.grow(),
+                };
+                let mut bsk = Basket::start(tob, psi);
+                if let Some(d) = self.memoized(tob) {
+                    bsk.put(Loc::Phi, Kid::Dtzd(d));
+                    trace!("new(β{}/ν{}, {}) -> ν{} is memoized", bk, ob, loc, tob);
+                } else {
+                    for k in self.object(tob).attrs.keys() {
+                        bsk.put(k.clone(), Kid::Empt);
+                    }
+                    bsk.put(Loc::Phi, Kid::Rqtd);
+                }
+                if self.unfinished_atom(&bsk) {
+                    *self.feeders.entry(psi).or_insert(0) += 1;
                 }
-                bsk.put(Loc::Phi, Kid::Rqtd);
                 self.baskets[id as usize] = bsk;
                 trace!("new(β{}/ν{}, {}) -> β{} created", bk, ob, loc, id);
                 id
             };
-            perf.hit(Transition::NEW);
-            let _ = &self.baskets[bk as usize].put(loc.clone(), Kid::Wait(nbk, Loc::Phi));
+            self.hit(perf, Transition::NEW, bk);
+            self.put_kid(bk, loc.clone(), Kid::Wait(nbk, Loc::Phi));
         }
         perf.tick(Transition::NEW);
     }
 
-    /// Suppose, the incoming locator is `^.0.@.2`. We have to find the right
-    /// object in the catalog of them and return the position of the found one
-    /// together with the suggested \psi.
-    #[allow(clippy::type_complexity)]
-    fn search(&self, bk: Bk, locator: &Locator) -> Result<(Ob, Bk, Option<(Bk, Loc)>), String> {
-        let mut bsk = self.basket(bk);
-        let mut attr = None;
-        let mut locs = locator.to_vec();
-        let mut ret = Err("Nothing found".to_string());
-        let mut ob = 0;
-        let mut log = vec![];
-        let mut psi: Bk = bsk.psi;
-        ret = loop {
-            if locs.is_empty() {
-                break ret;
-            }
-            let loc = locs.remove(0);
-            log.push(loc.to_string());
-            let next = match loc {
-                Loc::Root => ROOT_OB,
-                Loc::Pi => {
-                    if bsk.psi == ROOT_BK {
-                        return Err(format!("Object Φ doesn't have 𝜋: {}", join!(log)));
-                    }
-                    psi = bsk.psi;
-                    attr = Some((psi, Loc::Root));
-                    bsk = self.basket(psi);
-                    log.push(format!("𝜋=β{}/ν{}", psi, bsk.ob));
-                    bsk.ob
-                }
-                Loc::Obj(i) => i as Ob,
-                _ => match self.object(ob).attrs.get(&loc) {
-                    None => match self.object(ob).attrs.get(&Loc::Phi) {
-                        None => {
-                            return Err(format!(
-                                "Can't find {} in ν{} and there is no 𝜑: {}",
-                                loc,
-                                ob,
-                                join!(log)
-                            ))
-                        }
-                        Some((p, _psi)) => {
-                            locs.insert(0, loc.clone());
-                            attr = Some((attr.unwrap().0, loc));
-                            locs.splice(0..0, p.to_vec());
-                            log.push(format!("++{}", p));
-                            ob
-                        }
-                    },
-                    Some((p, _psi)) => {
-                        attr = Some((attr.unwrap().0, loc.clone()));
-                        locs.splice(0..0, p.to_vec());
-                        log.push(format!("+{}", p));
-                        ob
-                    }
-                },
-            };
-            ob = next;
-            ret = Ok((next, psi, attr.clone()))
-        };
-        if let Ok((next, _psi, _attr)) = ret.clone() {
-            if self.object(next).is_empty() {
-                return Err(format!(
-                    "Object ν{} is found by β{}.{}, but it's empty",
-                    next, bk, locator
-                ));
-            }
+    /// Make the pool of baskets twice bigger, if its limit allows,
+    /// and return the position of the first new basket.
+    fn grow(&mut self) -> Bk {
+        let len = self.baskets.len();
+        if len >= self.basket_limit {
+            panic!("No more empty baskets left in the pool of {}", len);
         }
-        trace!(
-            "find(β{}/ν{}, {}) -> (ν{}, β{}) : {} {}",
-            bk,
-            self.basket(bk).ob,
-            locator,
-            ret.clone().unwrap().0,
-            ret.clone().unwrap().1,
-            join!(log),
-            if let Some((bk, loc)) = ret.clone().unwrap().2 {
-                format!("[β{}.{}]", bk, loc)
-            } else {
-                "".to_string()
-            }
-        );
-        ret
+        let size = (len * 2).clamp(1, self.basket_limit);
+        self.baskets.resize_with(size, Basket::empty);
+        trace!("grow() -> {} baskets", size);
+        len as Bk
+    }
+
+    /// Find the object the locator points to, from the given basket,
+    /// using the resolver of this Emu.
+    pub(super) fn search(&self, bk: Bk, locator: &Locator) -> Result<Found, String> {
+        self.resolver.search(self, bk, locator)
+    }
+
+    /// How many baskets there are in the chain of ξ, from this
+    /// basket up to the root one.
+    fn psi_depth(&self, bk: Bk) -> usize {
+        let mut depth = 0;
+        let mut next = bk;
+        while next != ROOT_BK {
+            next = self.basket(next).psi;
+            depth += 1;
+        }
+        depth
     }
 
     /// Find already existing basket.
@@ -301,6 +266,52 @@ impl Emu {
         None
     }
 
+    /// Check that the stashed basket may be shared with the one,
+    /// which expects the object in the given context, and warn if not.
+    fn verify(&mut self, bk: Bk, psi: Bk) {
+        let bsk = self.basket(bk);
+        let (ob, actual) = (bsk.ob, bsk.psi);
+        let obj = self.object(ob);
+        if obj.delta.is_some() {
+            if !obj.attrs.is_empty() || obj.lambda.is_some() {
+                self.warn(Warning::MutableData { ob, bk });
+            }
+            return;
+        }
+        let impure = obj
+            .attrs
+            .values()
+            .any(|(locator, _)| locator.loc(0) == Some(&Loc::Pi));
+        if actual != psi {
+            self.warn(Warning::SharedConstant {
+                ob,
+                bk,
+                psi: actual,
+                expected: psi,
+            });
+        }
+        if impure {
+            self.warn(Warning::ImpureConstant { ob, bk });
+        }
+    }
+
+    /// Count the transition that just happened and report it to
+    /// the listener and the event writer, if there are any.
+    fn hit(&mut self, perf: &mut Perf, t: Transition, bk: Bk) {
+        if let Some(f) = self.transition_listener.as_mut() {
+            f(t, bk);
+        }
+        if self.opts.contains(&Opt::EventLog) {
+            let ob = self.basket(bk).ob;
+            if let Some(w) = self.event_writer.as_mut() {
+                if let Err(e) = writeln!(w, "{},{},{},ν{}", perf.cycles, t, bk, ob) {
+                    warn!("Can't write event of β{}: {}", bk, e);
+                }
+            }
+        }
+        perf.hit(t);
+    }
+
     pub fn object(&self, ob: Ob) -> &Object {
         &self.objects[ob]
     }
@@ -22,12 +22,41 @@
 
 pub mod atom;
 pub mod basket;
+pub mod builder;
 pub mod data;
 pub mod emu;
 pub mod loc;
 pub mod locator;
 pub mod object;
 pub mod perf;
+pub mod resolver;
+
+use crate::data::Data;
+use crate::emu::Emu;
+use crate::object::Ob;
+use std::str::FromStr;
+
+/// Check that two programs dataize to the same values, for each of
+/// the inputs, which are set into data objects with `Emu::set_data`.
+/// The first input they disagree on is reported.
+pub fn equiv(prog_a: &str, prog_b: &str, inputs: &[(Ob, Data)]) -> Result<(), String> {
+    let a = Emu::from_str(prog_a)?;
+    let b = Emu::from_str(prog_b)?;
+    for (ob, d) in inputs {
+        let mut first = a.clone_program();
+        let mut second = b.clone_program();
+        first.set_data(*ob, *d)?;
+        second.set_data(*ob, *d)?;
+        let (x, y) = (first.evaluate(), second.evaluate());
+        if x != y {
+            return Err(format!(
+                "With ν{} = 0x{:04X} the first program gives {:?}, while the second one {:?}",
+                ob, d, x, y
+            ));
+        }
+    }
+    Ok(())
+}
 
 #[cfg(test)]
 use simple_logger::SimpleLogger;
@@ -37,3 +66,38 @@ use simple_logger::SimpleLogger;
 fn init() {
     SimpleLogger::new().init().unwrap();
 }
+
+#[cfg(test)]
+const INC: &str = "
+    ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+    ν1(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+    ν2(𝜋) ↦ ⟦ λ ↦ int-inc, ρ ↦ ν1(𝜋) ⟧
+";
+
+#[test]
+fn finds_equivalent_programs() {
+    let add = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0001 ⟧
+    ";
+    assert_eq!(Ok(()), equiv(INC, add, &[(1, 0), (1, 41), (1, -7)]));
+}
+
+#[test]
+fn finds_first_divergence() {
+    let buggy = "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν2(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x0000 ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-times, ρ ↦ ν1(𝜋), 𝛼0 ↦ ν3(𝜋) ⟧
+        ν3(𝜋) ↦ ⟦ Δ ↦ 0x0002 ⟧
+    ";
+    let err = equiv(INC, buggy, &[(1, 1), (1, 5)]).unwrap_err();
+    assert!(err.starts_with("With ν1 = 0x0005"), "{}", err);
+    assert!(
+        err.contains("Value(6)") && err.contains("Value(10)"),
+        "{}",
+        err
+    );
+}
@@ -37,35 +37,54 @@ pub enum Loc {
     Obj(Ob),
 }
 
+/// Besides the glyphs that `Display` prints, it understands ASCII ones
+/// and look-alike Greek or math letters, like `φ` for `𝜑`.
 impl FromStr for Loc {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         lazy_static! {
-            static ref RE_ARG: Regex = Regex::new("^𝛼?(\\d+)$").unwrap();
-            static ref RE_OBJ: Regex = Regex::new("^ν(\\d+)$").unwrap();
+            static ref RE_ARG: Regex = Regex::new("^[𝛼α]?(\\d+)$").unwrap();
+            static ref RE_OBJ: Regex = Regex::new("^[ν𝜈v](\\d+)$").unwrap();
         }
         if let Some(caps) = RE_ARG.captures(s) {
-            Ok(Loc::Attr(
-                caps.get(1).unwrap().as_str().parse::<i8>().unwrap(),
-            ))
+            Ok(Loc::Attr(caps[1].parse::<i8>().map_err(|e| {
+                format!("Can't parse attribute '{}': {}", s, e)
+            })?))
         } else if let Some(caps) = RE_OBJ.captures(s) {
-            Ok(Loc::Obj(
-                caps.get(1).unwrap().as_str().parse::<Ob>().unwrap(),
-            ))
+            Ok(Loc::Obj(caps[1].parse::<Ob>().map_err(|e| {
+                format!("Can't parse object '{}': {}", s, e)
+            })?))
         } else {
             match s {
-                "Φ" | "Q" => Ok(Loc::Root),
-                "Δ" | "D" => Ok(Loc::Delta),
-                "𝜋" | "P" => Ok(Loc::Pi),
-                "ρ" | "^" => Ok(Loc::Rho),
-                "𝜑" | "@" => Ok(Loc::Phi),
-                "σ" | "&" => Ok(Loc::Sigma),
+                "Φ" | "𝛷" | "Q" => Ok(Loc::Root),
+                "Δ" | "∆" | "𝛥" | "D" => Ok(Loc::Delta),
+                "𝜋" | "π" | "P" => Ok(Loc::Pi),
+                "ρ" | "𝜌" | "^" => Ok(Loc::Rho),
+                "𝜑" | "φ" | "ϕ" | "@" => Ok(Loc::Phi),
+                "σ" | "𝜎" | "&" => Ok(Loc::Sigma),
                 _ => Err(format!("Unknown loc: '{}'", s)),
             }
         }
     }
 }
 
+impl Loc {
+    /// Print it with ASCII glyphs only, like `P`, `0`, or `v5`,
+    /// which `from_str` understands as well.
+    pub fn to_ascii(&self) -> String {
+        match self {
+            Loc::Root => "Q".to_owned(),
+            Loc::Rho => "^".to_owned(),
+            Loc::Delta => "D".to_owned(),
+            Loc::Phi => "@".to_owned(),
+            Loc::Pi => "P".to_owned(),
+            Loc::Sigma => "&".to_owned(),
+            Loc::Attr(i) => format!("{}", i),
+            Loc::Obj(i) => format!("v{}", i),
+        }
+    }
+}
+
 impl fmt::Display for Loc {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&match self {
@@ -99,3 +118,31 @@ pub fn parses_and_prints(#[case] txt: &str) {
     let loc2 = Loc::from_str(&loc1.to_string()).unwrap();
     assert_eq!(loc1, loc2)
 }
+
+#[rstest]
+#[case("𝛷", Loc::Root)]
+#[case("∆", Loc::Delta)]
+#[case("𝛥", Loc::Delta)]
+#[case("π", Loc::Pi)]
+#[case("𝜌", Loc::Rho)]
+#[case("φ", Loc::Phi)]
+#[case("ϕ", Loc::Phi)]
+#[case("𝜎", Loc::Sigma)]
+#[case("α3", Loc::Attr(3))]
+#[case("𝜈7", Loc::Obj(7))]
+pub fn parses_alternative_spellings(#[case] txt: &str, #[case] loc: Loc) {
+    let parsed = Loc::from_str(txt).unwrap();
+    assert_eq!(loc, parsed);
+    assert_eq!(loc.to_string(), parsed.to_string());
+    assert_ne!(txt, parsed.to_string());
+}
+
+#[rstest]
+#[case(Loc::Root, "Q")]
+#[case(Loc::Pi, "P")]
+#[case(Loc::Attr(3), "3")]
+#[case(Loc::Obj(5), "v5")]
+pub fn prints_ascii(#[case] loc: Loc, #[case] txt: &str) {
+    assert_eq!(txt, loc.to_ascii());
+    assert_eq!(loc, Loc::from_str(txt).unwrap());
+}
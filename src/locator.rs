@@ -24,6 +24,9 @@ use rstest::rstest;
 use std::fmt;
 use std::str::FromStr;
 
+/// The longest locator, in segments, that `from_str` accepts.
+pub const MAX_LOCATOR_LENGTH: usize = 256;
+
 /// Locator is a chain of attributes connected with dots,
 /// for example `𝜋.𝜋.𝛼0` is a locator.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -79,13 +82,33 @@ impl Locator {
     pub fn to_vec(&self) -> Vec<Loc> {
         self.locs.clone()
     }
-}
 
-type CheckFn = fn(&Locator) -> Option<String>;
+    /// How many attributes are there in the locator.
+    pub fn len(&self) -> usize {
+        self.locs.len()
+    }
 
-impl FromStr for Locator {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    /// This locator has no attributes at all.
+    pub fn is_empty(&self) -> bool {
+        self.locs.is_empty()
+    }
+
+    /// Print it with ASCII glyphs only, like `P.P.0`.
+    pub fn to_ascii(&self) -> String {
+        self.locs
+            .iter()
+            .map(Loc::to_ascii)
+            .collect::<Vec<String>>()
+            .join(".")
+    }
+
+    /// Parse it, like `from_str` does, but with a different
+    /// limit of its length.
+    pub fn parse_with_limit(s: &str, max: usize) -> Result<Locator, String> {
+        let segments = s.split('.').count();
+        if segments > max {
+            return Err(format!("Locator too long: {} segments", segments));
+        }
         lazy_static! {
             static ref CHECKS: [CheckFn; 4] = [
                 |p: &Locator| -> Option<String> {
@@ -107,7 +130,7 @@ impl FromStr for Locator {
                         .map(|v| format!("{} can't start a locator", v))
                 },
                 |p: &Locator| {
-                    if matches!(p.locs[0], Loc::Obj(_)) && p.locs.len() > 1 {
+                    if matches!(p.locs[0], Loc::Obj(_)) && p.len() > 1 {
                         Some(format!(
                             "{} can only be the first and only locator",
                             p.locs[0]
@@ -119,7 +142,10 @@ impl FromStr for Locator {
             ];
         }
         let p = Locator {
-            locs: s.split('.').map(|i| Loc::from_str(i).unwrap()).collect(),
+            locs: s
+                .split('.')
+                .map(Loc::from_str)
+                .collect::<Result<Vec<Loc>, String>>()?,
         };
         for check in CHECKS.iter() {
             if let Some(msg) = (check)(&p) {
@@ -130,6 +156,15 @@ impl FromStr for Locator {
     }
 }
 
+type CheckFn = fn(&Locator) -> Option<String>;
+
+impl FromStr for Locator {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Locator::parse_with_limit(s, MAX_LOCATOR_LENGTH)
+    }
+}
+
 impl fmt::Display for Locator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(
@@ -195,3 +230,44 @@ pub fn fetches_loc_from_locator(
 ) {
     assert_eq!(*ph!(&locator).loc(idx).unwrap(), expected);
 }
+
+#[test]
+pub fn counts_its_length() {
+    assert_eq!(3, ph!("P.0.@").len());
+    assert!(!ph!("P.0.@").is_empty());
+}
+
+#[test]
+pub fn empty_locator_is_empty() {
+    let locator = Locator::from_vec(vec![]);
+    assert_eq!(0, locator.len());
+    assert!(locator.is_empty());
+}
+
+#[test]
+pub fn parses_locator_at_length_limit() {
+    let text = vec!["𝜋"; MAX_LOCATOR_LENGTH].join(".");
+    assert_eq!(MAX_LOCATOR_LENGTH, ph!(&text).len());
+    assert_eq!(3, Locator::parse_with_limit("𝜋.𝜋.𝛼0", 3).unwrap().len());
+}
+
+#[test]
+pub fn rejects_too_long_locator() {
+    let text = vec!["𝜋"; MAX_LOCATOR_LENGTH + 1].join(".");
+    assert_eq!(
+        "Locator too long: 257 segments",
+        Locator::from_str(&text).unwrap_err()
+    );
+    assert_eq!(
+        "Locator too long: 3 segments",
+        Locator::parse_with_limit("𝜋.𝜋.𝛼0", 2).unwrap_err()
+    );
+}
+
+#[test]
+pub fn prints_ascii() {
+    let locator = ph!("𝜋.𝜋.𝛼0");
+    assert_eq!("P.P.0", locator.to_ascii());
+    assert_eq!(locator, ph!(&locator.to_ascii()));
+    assert_eq!("v7", ph!("ν7").to_ascii());
+}
@@ -18,7 +18,9 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use crate::atom;
 use crate::atom::*;
+use crate::data;
 use crate::data::Data;
 use crate::loc::Loc;
 use crate::locator::Locator;
@@ -31,11 +33,13 @@ use std::str::FromStr;
 
 pub type Ob = usize;
 
+#[derive(Clone)]
 pub struct Object {
     pub delta: Option<Data>,
     pub lambda: Option<(String, Atom)>,
     pub constant: bool,
     pub attrs: HashMap<Loc, (Locator, bool)>,
+    pub label: Option<String>,
 }
 
 impl Object {
@@ -45,6 +49,7 @@ impl Object {
             lambda: None,
             constant: false,
             attrs: HashMap::new(),
+            label: None,
         }
     }
 
@@ -54,6 +59,7 @@ impl Object {
             lambda: None,
             constant: true,
             attrs: HashMap::new(),
+            label: None,
         }
     }
 
@@ -63,6 +69,7 @@ impl Object {
             lambda: Some((n, a)),
             constant: false,
             attrs: HashMap::new(),
+            label: None,
         }
     }
 
@@ -95,6 +102,26 @@ impl Object {
         self
     }
 
+    /// Add a new attribute, but only if there is no attribute
+    /// with the same name yet:
+    ///
+    /// ```
+    /// use phie::loc::Loc;
+    /// use phie::locator::Locator;
+    /// use phie::object::Object;
+    /// use std::str::FromStr;
+    /// use phie::ph;
+    /// let mut obj = Object::open();
+    /// assert!(obj.try_push(Loc::Phi, ph!("ν13"), false).is_ok());
+    /// assert!(obj.try_push(Loc::Phi, ph!("ν14"), false).is_err());
+    /// ```
+    pub fn try_push(&mut self, loc: Loc, p: Locator, xi: bool) -> Result<&mut Object, String> {
+        if self.attrs.contains_key(&loc) {
+            return Err(format!("The attribute {} is already set", loc));
+        }
+        Ok(self.push(loc, p, xi))
+    }
+
     /// You can do the same, but with "fluent interface" of the `Object`.
     ///
     /// ```
@@ -108,24 +135,82 @@ impl Object {
     ///   .with(Loc::Attr(0), ph!("ρ.1"), false);
     /// ```
     pub fn with(&self, loc: Loc, p: Locator, xi: bool) -> Object {
-        let mut obj = self.copy();
+        let mut obj = self.clone();
         obj.attrs.insert(loc, (p, xi));
         obj
     }
 
+    /// Give it a name, which is only used for debugging:
+    ///
+    /// ```
+    /// use phie::object::Object;
+    /// let obj = Object::dataic(42).with_label("answer");
+    /// assert_eq!("⟦! Δ↦0x002A⟧ # answer", obj.to_string());
+    /// ```
+    pub fn with_label(&self, label: &str) -> Object {
+        let mut obj = self.clone();
+        obj.label = Some(label.to_string());
+        obj
+    }
+
+    /// How many arguments `𝛼0`, `𝛼1`, etc. it has.
+    pub fn arity(&self) -> usize {
+        self.attrs
+            .keys()
+            .filter(|loc| matches!(loc, Loc::Attr(_)))
+            .count()
+    }
+
+    /// It has the `ρ` attribute.
+    pub fn has_rho(&self) -> bool {
+        self.attrs.contains_key(&Loc::Rho)
+    }
+
+    /// Check that it's either an atom, or data, or an object
+    /// with attributes, but not a mix of them.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.lambda.is_some() && self.delta.is_some() {
+            return Err("An object can't have both λ and Δ".to_string());
+        }
+        if self.attrs.contains_key(&Loc::Phi) {
+            if self.lambda.is_some() {
+                return Err("An object with λ can't have 𝜑".to_string());
+            }
+            if self.delta.is_some() {
+                return Err("An object with Δ can't have 𝜑".to_string());
+            }
+        }
+        Ok(())
+    }
+
     pub fn as_constant(&self) -> Object {
-        let mut obj = self.copy();
+        let mut obj = self.clone();
         obj.constant = true;
         obj
     }
 
-    fn copy(&self) -> Object {
-        let mut obj = Object::open();
-        obj.lambda = self.lambda.clone();
-        obj.constant = self.constant;
-        obj.delta = self.delta;
-        obj.attrs.extend(self.attrs.clone().into_iter());
-        obj
+    /// The suffix printed after the locator of an attribute: `(ξ)` if
+    /// it's `xi`, `(𝜋)` if it starts with an object, or nothing.
+    pub fn attr_suffix(locator: &Locator, xi: bool) -> &'static str {
+        if xi {
+            "(ξ)"
+        } else if matches!(locator.loc(0), Some(Loc::Obj(_))) {
+            "(𝜋)"
+        } else {
+            ""
+        }
+    }
+
+    /// Split the text of an attribute's locator into the locator itself
+    /// and two flags: whether it had `(ξ)` and whether it had `(𝜋)`.
+    pub fn strip_attr_suffix(s: &str) -> (String, bool, bool) {
+        if let Some(locator) = suffixed(s, "ξ") {
+            (locator.to_string(), true, false)
+        } else if let Some(locator) = suffixed(s, "𝜋") {
+            (locator.to_string(), false, true)
+        } else {
+            (s.trim().to_string(), false, false)
+        }
     }
 }
 
@@ -140,23 +225,23 @@ impl fmt::Display for Object {
         }
         for i in self.attrs.iter() {
             let (attr, (locator, xi)) = i;
-            parts.push(
-                format!("{}↦{}", attr, locator)
-                    + &(if *xi {
-                        "(ξ)".to_string()
-                    } else if matches!(locator.loc(0).unwrap(), Loc::Obj(_)) {
-                        "(𝜋)".to_string()
-                    } else {
-                        "".to_string()
-                    }),
-            );
+            parts.push(format!(
+                "{}↦{}{}",
+                attr,
+                locator,
+                Object::attr_suffix(locator, *xi)
+            ));
         }
         parts.sort();
         write!(
             f,
-            "⟦{}{}⟧",
+            "⟦{}{}⟧{}",
             if self.constant { "! " } else { "" },
-            parts.iter().join(", ")
+            parts.iter().join(", "),
+            match &self.label {
+                Some(label) => format!(" # {}", label),
+                None => "".to_string(),
+            }
         )
     }
 }
@@ -164,74 +249,98 @@ impl fmt::Display for Object {
 impl FromStr for Object {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new("⟦(!?)(.*)⟧").unwrap();
+        Object::parse(s, false)
+    }
+}
+
+impl Object {
+    /// Parse it, like `from_str` does, but let unknown lambdas in,
+    /// as atoms that never finish.
+    pub fn from_str_lenient(s: &str) -> Result<Object, String> {
+        Object::parse(s, true)
+    }
+
+    /// Parse the text, letting unknown lambdas in, if it's lenient,
+    /// or failing on them otherwise.
+    fn parse(s: &str, lenient: bool) -> Result<Object, String> {
+        let re = Regex::new("^\\s*(?:ν\\d+\\([^)]*\\)\\s*↦\\s*)?⟦(!?)(.*?)⟧(?:\\s*#\\s*(.*))?$")
+            .unwrap();
         let mut obj = Object::open();
-        let caps = re.captures(s).unwrap();
-        for pair in caps
-            .get(2)
-            .unwrap()
-            .as_str()
-            .trim()
-            .split(',')
-            .map(|t| t.trim())
-        {
+        let caps = re
+            .captures(s)
+            .ok_or(format!("Can't parse the object: '{}'", s))?;
+        for pair in caps[2].trim().split(',').map(|t| t.trim()) {
             let (i, p) = pair
                 .split('↦')
                 .map(|t| t.trim())
                 .collect_tuple()
                 .ok_or(format!("Can't split '{}' in two parts at '{}'", pair, s))?;
-            match i.chars().take(1).last().unwrap() {
+            match i
+                .chars()
+                .next()
+                .ok_or(format!("Attribute name is missing in '{}'", s))?
+            {
                 'λ' => {
-                    obj = Object::atomic(
-                        p.to_string(),
-                        match p {
-                            "int-times" => int_times,
-                            "int-div" => int_div,
-                            "int-sub" => int_sub,
-                            "int-add" => int_add,
-                            "int-neg" => int_neg,
-                            "bool-if" => bool_if,
-                            "int-less" => int_less,
-                            _ => panic!("Unknown lambda '{}'", p),
-                        },
-                    );
+                    let func = match atom::by_name(p) {
+                        Some(func) => func,
+                        None if lenient => atom::unknown,
+                        None => return Err(format!("Unknown lambda '{}' in '{}'", p, s)),
+                    };
+                    obj.lambda = Some((p.to_string(), func));
                 }
                 'Δ' => {
-                    let hex: String = p.chars().skip(2).collect();
-                    let data: Data = Data::from_str_radix(&hex, 16)
-                        .unwrap_or_else(|_| panic!("Can't parse hex '{}' in '{}'", hex, s));
-                    obj = Object::dataic(data);
+                    let (p, width) = match p.split_once(':') {
+                        Some((v, w)) => (v.trim(), w.trim()),
+                        None => (p, "i16"),
+                    };
+                    if width != "i16" {
+                        return Err(format!(
+                            "Data is i16 only, the width '{}' is not supported in '{}'",
+                            width, s
+                        ));
+                    }
+                    let hex = p
+                        .strip_prefix("0x")
+                        .ok_or(format!("Data '{}' must start with 0x in '{}'", p, s))?
+                        .replace('_', "");
+                    let data: Data =
+                        data::from_hex(&hex).map_err(|e| format!("{} in '{}'", e, s))?;
+                    obj.delta = Some(data);
+                    obj.constant = true;
                 }
                 _ => {
-                    let tail = if p.ends_with("(𝜋)") {
-                        p.chars().take(p.len() - "(𝜋)".len() - 1).collect()
-                    } else {
-                        p.to_string()
-                    };
-                    let xi_suffix = "(ξ)";
-                    let xi = tail.ends_with(xi_suffix);
-                    let locator = if xi {
-                        tail.chars()
-                            .take(tail.len() - xi_suffix.len() - 1)
-                            .collect()
-                    } else {
-                        tail.to_string()
-                    };
-                    obj.push(
-                        Loc::from_str(i).unwrap(),
-                        Locator::from_str(&locator).unwrap(),
+                    let (locator, xi, _) = Object::strip_attr_suffix(p);
+                    obj.try_push(
+                        Loc::from_str(i).map_err(|e| format!("{} in '{}'", e, s))?,
+                        Locator::from_str(&locator).map_err(|e| format!("{} in '{}'", e, s))?,
                         xi,
-                    );
+                    )
+                    .map_err(|e| format!("{} in '{}'", e, s))?;
                 }
             };
         }
-        if !caps.get(1).unwrap().as_str().is_empty() {
+        if !caps[1].is_empty() {
             obj.constant = true;
         }
+        if let Some(label) = caps.get(3) {
+            obj.label = Some(label.as_str().trim().to_string());
+        }
+        obj.validate().map_err(|e| format!("{} in '{}'", e, s))?;
         Ok(obj)
     }
 }
 
+/// Strip the `(ξ)` or `(𝜋)` suffix from the locator, tolerating
+/// spaces around and inside the brackets, like in `ν2 ( ξ )`.
+fn suffixed<'a>(p: &'a str, name: &str) -> Option<&'a str> {
+    let inner = p
+        .trim_end()
+        .strip_suffix(')')?
+        .trim_end()
+        .strip_suffix(name)?;
+    Some(inner.trim_end().strip_suffix('(')?.trim_end())
+}
+
 #[cfg(test)]
 use crate::ph;
 
@@ -277,3 +386,170 @@ fn prints_and_parses_some_object(#[case] text: String) {
     let text3 = obj2.to_string();
     assert_eq!(text2, text3);
 }
+
+#[test]
+fn prints_and_parses_negative_delta() {
+    for d in [-1, -42, Data::MIN] {
+        let text = Object::dataic(d).to_string();
+        assert_eq!(Some(d), Object::from_str(&text).unwrap().delta, "{}", text);
+    }
+}
+
+#[test]
+fn reports_overflowing_delta() {
+    let err = Object::from_str("⟦ Δ ↦ 0x1FFFF ⟧").err().unwrap();
+    assert!(
+        err.contains("Hex value 0x1FFFF overflows Data width"),
+        "{}",
+        err
+    );
+}
+
+#[rstest]
+#[case(
+    "⟦ Δ ↦ 0x0001_0000 ⟧",
+    "overflows Data width of 16 bits, use --wide-data"
+)]
+#[case("⟦ Δ ↦ 0x0001_0000 : i32 ⟧", "the width 'i32' is not supported")]
+fn explains_too_wide_delta(#[case] text: &str, #[case] hint: &str) {
+    let err = Object::from_str(text).err().unwrap();
+    assert!(err.contains(hint), "{}", err);
+}
+
+#[test]
+fn parses_delta_with_separators_and_width() {
+    let obj = Object::from_str("⟦ Δ ↦ 0x00_2A : i16 ⟧").unwrap();
+    assert_eq!(Some(42), obj.delta);
+}
+
+#[test]
+fn prints_and_parses_label() {
+    let obj = Object::open()
+        .with(Loc::Phi, ph!("ν3"), false)
+        .with_label("foo");
+    let text = obj.to_string();
+    assert_eq!("⟦𝜑↦ν3(𝜋)⟧ # foo", text);
+    let obj2 = Object::from_str(&text).unwrap();
+    assert_eq!(Some("foo".to_string()), obj2.label);
+    assert_eq!(text, obj2.to_string());
+}
+
+#[test]
+fn rejects_duplicate_attribute() {
+    let err = Object::from_str("⟦ 𝜑 ↦ ν1, 𝜑 ↦ ν2 ⟧").err().unwrap();
+    assert!(err.starts_with("The attribute 𝜑 is already set"), "{}", err);
+}
+
+#[rstest]
+#[case("⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν3(𝜋) ⟧")]
+#[case("⟦ 𝜑 ↦ ν2 (ξ), 𝛼0 ↦ ν3 (𝜋) ⟧")]
+#[case("⟦𝜑↦ν2(ξ),𝛼0↦ν3(𝜋)⟧")]
+#[case("⟦  𝜑   ↦   ν2  ( ξ ) ,  𝛼0  ↦  ν3 ( 𝜋 )  ⟧")]
+#[case("⟦ 𝜑 ↦\tν2\t(ξ), 𝛼0 ↦ ν3\t(𝜋)\t⟧")]
+fn parses_with_any_spacing(#[case] text: &str) {
+    let obj = Object::from_str(text).unwrap();
+    assert_eq!("⟦𝛼0↦ν3(𝜋), 𝜑↦ν2(ξ)⟧", obj.to_string());
+}
+
+#[test]
+fn counts_arguments() {
+    let obj = Object::from_str("⟦ λ ↦ int-add, ρ ↦ ν1, 𝛼0 ↦ ν2 ⟧").unwrap();
+    assert_eq!(1, obj.arity());
+    assert!(obj.has_rho());
+    let data = Object::dataic(42);
+    assert_eq!(0, data.arity());
+    assert!(!data.has_rho());
+}
+
+#[test]
+fn rejects_atom_with_data() {
+    let err = Object::from_str("⟦ λ ↦ int-add, Δ ↦ 0x0001 ⟧")
+        .err()
+        .unwrap();
+    assert!(
+        err.starts_with("An object can't have both λ and Δ"),
+        "{}",
+        err
+    );
+    assert!(Object::atomic("int-add".to_string(), int_add)
+        .with(Loc::Phi, ph!("ν1"), false)
+        .validate()
+        .is_err());
+    assert!(Object::dataic(42).validate().is_ok());
+}
+
+#[test]
+fn keeps_attributes_before_lambda() {
+    let obj = Object::from_str("⟦ ρ ↦ ν1, λ ↦ int-neg ⟧").unwrap();
+    assert_eq!("⟦λ↦int-neg, ρ↦ν1(𝜋)⟧", obj.to_string());
+}
+
+#[rstest]
+#[case("")]
+#[case("⟦⟧")]
+#[case("⟦ ⟧")]
+#[case("⟦ Δ ↦ 0x ⟧")]
+#[case("⟦ Δ ↦ 2A ⟧")]
+#[case("⟦ Δ ↦ ⟧")]
+#[case("⟦ ↦ ν1 ⟧")]
+#[case("⟦ 𝜑 ↦ ν ⟧")]
+#[case("⟦ 𝜑 ↦ (𝜋) ⟧")]
+#[case("⟦ 𝜑 ↦ (ξ) ⟧")]
+#[case("⟦ 𝛼999 ↦ ν1 ⟧")]
+#[case("⟦ 𝜑 ↦ ν99999999999999999999999 ⟧")]
+#[case("⟦ 𝜑 ↦ 𝜋.$ ⟧")]
+#[case("⟦ λ ↦ unknown ⟧")]
+#[case("⟦ 𝜑 ↦ ν1 ↦ ν2 ⟧")]
+#[case("⟦ Δ ↦ 0x0001, λ ↦ int-neg ⟧")]
+#[case("⟦ Δ ↦ 0x0001, 𝜑 ↦ ν1 ⟧")]
+#[case("⟦ λ ↦ int-neg, 𝜑 ↦ ν1 ⟧")]
+#[case("⟦ 𝜑 ↦ ν1 ⟧ ν2")]
+#[case("⟦ 𝜑 ↦ ν1 ⟧ ⟦ 𝜑 ↦ ν2 ⟧")]
+#[case("junk ⟦ Δ ↦ 0x0001 ⟧")]
+fn rejects_broken_object(#[case] text: &str) {
+    assert!(Object::from_str(text).is_err());
+}
+
+#[test]
+fn recognizes_exactly_builtin_atoms() {
+    for (name, _, _) in atom::builtins() {
+        let obj = Object::from_str(&format!("⟦ λ ↦ {}, ρ ↦ ν1 ⟧", name)).unwrap();
+        assert_eq!(*name, obj.lambda.unwrap().0);
+    }
+    assert!(Object::from_str("⟦ λ ↦ int-mod, ρ ↦ ν1 ⟧").is_err());
+}
+
+#[rstest]
+#[case("𝜋.𝛼0", false, "", "𝜋.𝛼0", false, false)]
+#[case("ν3", true, "(ξ)", "ν3(ξ)", true, false)]
+#[case("ν3", false, "(𝜋)", "ν3(𝜋)", false, true)]
+fn encodes_attr_suffix(
+    #[case] locator: &str,
+    #[case] xi: bool,
+    #[case] suffix: &str,
+    #[case] text: &str,
+    #[case] has_xi: bool,
+    #[case] has_pi: bool,
+) {
+    let locator = ph!(locator);
+    assert_eq!(suffix, Object::attr_suffix(&locator, xi));
+    assert_eq!(
+        (locator.to_string(), has_xi, has_pi),
+        Object::strip_attr_suffix(text)
+    );
+    assert_eq!(
+        (locator.to_string(), has_xi, has_pi),
+        Object::strip_attr_suffix(&format!("{}{}", locator, Object::attr_suffix(&locator, xi)))
+    );
+}
+
+#[test]
+fn parses_unknown_lambda_leniently() {
+    assert!(Object::from_str("⟦ λ ↦ int-mod, ρ ↦ ν1 ⟧").is_err());
+    let obj = Object::from_str_lenient("⟦ λ ↦ int-mod, ρ ↦ ν1 ⟧").unwrap();
+    assert_eq!("⟦λ↦int-mod, ρ↦ν1(𝜋)⟧", obj.to_string());
+    assert!(Object::from_str_lenient("⟦ λ ↦ int-add, ρ ↦ ν1 ⟧")
+        .unwrap()
+        .lambda
+        .is_some());
+}
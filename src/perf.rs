@@ -19,10 +19,14 @@
 // SOFTWARE.
 
 use itertools::Itertools;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::time::Duration;
 
-#[derive(Hash, Eq, PartialEq, strum_macros::Display)]
+#[derive(
+    Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, strum_macros::Display,
+)]
 pub enum Transition {
     CPY,
     DEL,
@@ -32,12 +36,31 @@ pub enum Transition {
     FND,
 }
 
+#[derive(Clone)]
 pub struct Perf {
     pub cycles: usize,
     pub peak: usize,
+    pub overflows: usize,
+    pub reads: usize,
+    pub fanouts: HashMap<usize, usize>,
     pub atoms: HashMap<String, usize>,
     pub hits: HashMap<Transition, usize>,
     pub ticks: HashMap<Transition, usize>,
+    pub atom_time: HashMap<String, Duration>,
+}
+
+/// A snapshot of `Perf`, with ordered maps, good for comparing
+/// whole profiles in tests and for serializing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PerfReport {
+    pub cycles: usize,
+    pub peak: usize,
+    pub overflows: usize,
+    pub reads: usize,
+    pub fanouts: BTreeMap<usize, usize>,
+    pub atoms: BTreeMap<String, usize>,
+    pub hits: BTreeMap<Transition, usize>,
+    pub ticks: BTreeMap<Transition, usize>,
 }
 
 impl Default for Perf {
@@ -52,8 +75,12 @@ impl Perf {
             atoms: HashMap::new(),
             ticks: HashMap::new(),
             hits: HashMap::new(),
+            atom_time: HashMap::new(),
             cycles: 0,
             peak: 0,
+            overflows: 0,
+            reads: 0,
+            fanouts: HashMap::new(),
         }
     }
 
@@ -69,6 +96,21 @@ impl Perf {
         *self.atoms.entry(a).or_insert(0) += 1;
     }
 
+    /// Remember that a value has just been propagated to this
+    /// many waiting kids at once.
+    pub fn fanout(&mut self, waiters: usize) {
+        *self.fanouts.entry(waiters).or_insert(0) += 1;
+    }
+
+    /// The largest number of kids that one value was propagated to.
+    pub fn max_fanout(&self) -> usize {
+        self.fanouts.keys().max().copied().unwrap_or(0)
+    }
+
+    pub fn time(&mut self, a: String, d: Duration) {
+        *self.atom_time.entry(a).or_insert(Duration::ZERO) += d;
+    }
+
     pub fn peak(&mut self, s: usize) {
         if self.peak < s {
             self.peak = s
@@ -86,30 +128,102 @@ impl Perf {
     pub fn total_atoms(&self) -> usize {
         self.atoms.values().sum()
     }
+
+    /// Take a snapshot of the counters.
+    pub fn to_report(&self) -> PerfReport {
+        PerfReport {
+            cycles: self.cycles,
+            peak: self.peak,
+            overflows: self.overflows,
+            reads: self.reads,
+            fanouts: self.fanouts.clone().into_iter().collect(),
+            atoms: self.atoms.clone().into_iter().collect(),
+            hits: self.hits.clone().into_iter().collect(),
+            ticks: self.ticks.clone().into_iter().collect(),
+        }
+    }
+
+    /// Summarize it in one line, good for logs.
+    pub fn to_compact(&self) -> String {
+        format!(
+            "cycles={} peak={} atoms={} hits={}",
+            self.cycles,
+            self.peak,
+            self.total_atoms(),
+            self.total_hits()
+        )
+    }
 }
 
 macro_rules! print {
-    ($lines:expr, $title:expr, $list:expr, $total:expr) => {
+    ($lines:expr, $aligned:expr, $title:expr, $list:expr, $total:expr) => {
+        let width = if $aligned {
+            $list
+                .keys()
+                .map(|k| k.to_string().chars().count())
+                .chain(std::iter::once("Total".len()))
+                .max()
+                .unwrap()
+                + 1
+        } else {
+            0
+        };
         $lines.push(format!("{}:", $title));
         $lines.extend(
             $list
                 .iter()
-                .map(|(t, c)| format!("\t{}: {}", t, c))
+                .map(|(t, c)| format!("\t{:<w$} {}", format!("{}:", t), c, w = width))
                 .sorted(),
         );
-        $lines.push(format!("\tTotal: {}", $total));
+        $lines.push(format!("\t{:<w$} {}", "Total:", $total, w = width));
     };
 }
 
-impl fmt::Display for Perf {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl Perf {
+    /// Print it like `Display` does, but without aligning the counts
+    /// of atoms, ticks, and hits in columns.
+    pub fn to_verbose(&self) -> String {
+        self.render(false)
+    }
+
+    fn render(&self, aligned: bool) -> String {
         let mut lines = vec![];
         lines.push(format!("Cycles: {}", self.cycles));
         lines.push(format!("Peak: {}", self.peak));
-        print!(lines, "Atoms", self.atoms, self.total_atoms());
-        print!(lines, "Ticks", self.ticks, self.total_ticks());
-        print!(lines, "Hits", self.hits, self.total_hits());
-        f.write_str(lines.join("\n").as_str())
+        if self.overflows > 0 {
+            lines.push(format!("Overflows: {}", self.overflows));
+        }
+        if self.reads > 0 {
+            lines.push(format!("Reads: {}", self.reads));
+        }
+        print!(lines, aligned, "Atoms", self.atoms, self.total_atoms());
+        print!(lines, aligned, "Ticks", self.ticks, self.total_ticks());
+        print!(lines, aligned, "Hits", self.hits, self.total_hits());
+        if !self.fanouts.is_empty() {
+            lines.push("Fan-outs:".to_string());
+            lines.extend(
+                self.fanouts
+                    .iter()
+                    .sorted()
+                    .map(|(n, c)| format!("\t{} waiters: {}", n, c)),
+            );
+        }
+        if !self.atom_time.is_empty() {
+            lines.push("Atom time:".to_string());
+            lines.extend(
+                self.atom_time
+                    .iter()
+                    .map(|(a, d)| format!("\t{}: {:?}", a, d))
+                    .sorted(),
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for Perf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.render(true).as_str())
     }
 }
 
@@ -117,7 +231,7 @@ impl fmt::Display for Perf {
 pub fn simple_increment() {
     let mut perf = Perf::new();
     perf.hit(Transition::DEL);
-    assert!(perf.to_string().contains("DEL: 1"));
+    assert!(perf.to_verbose().contains("DEL: 1"));
 }
 
 #[test]
@@ -126,5 +240,44 @@ pub fn sorts_them() {
     perf.hit(Transition::DEL);
     perf.hit(Transition::PPG);
     perf.hit(Transition::NEW);
-    assert!(perf.to_string().contains("DEL: 1\n\tNEW: 1\n\tPPG: 1"));
+    assert!(perf.to_verbose().contains("DEL: 1\n\tNEW: 1\n\tPPG: 1"));
+}
+
+#[test]
+pub fn aligns_counts() {
+    let mut perf = Perf::new();
+    perf.atom("int-add".to_string());
+    perf.atom("bool-if".to_string());
+    perf.atom("int-sub".to_string());
+    perf.atom("int-sub".to_string());
+    assert!(perf
+        .to_string()
+        .contains("Atoms:\n\tbool-if: 1\n\tint-add: 1\n\tint-sub: 2\n\tTotal:   4"));
+}
+
+#[test]
+pub fn prints_compact() {
+    let mut perf = Perf::new();
+    perf.cycles = 12;
+    perf.peak(5);
+    perf.hit(Transition::CPY);
+    perf.hit(Transition::NEW);
+    perf.atom("int-add".to_string());
+    assert_eq!("cycles=12 peak=5 atoms=1 hits=2", perf.to_compact());
+}
+
+#[test]
+pub fn prints_atom_time() {
+    let mut perf = Perf::new();
+    perf.time("int-add".to_string(), Duration::from_micros(3));
+    perf.time("int-add".to_string(), Duration::from_micros(4));
+    assert!(perf.to_string().contains("Atom time:\n\tint-add: 7µs"));
+}
+
+#[test]
+pub fn prints_overflows() {
+    let mut perf = Perf::new();
+    assert!(!perf.to_string().contains("Overflows"));
+    perf.overflows = 2;
+    assert!(perf.to_string().contains("Peak: 0\nOverflows: 2\n"));
 }
@@ -0,0 +1,224 @@
+// Copyright (c) 2022 Yegor Bugayenko
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included
+// in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NON-INFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::basket::Bk;
+use crate::emu::{Emu, ROOT_BK, ROOT_OB};
+use crate::loc::Loc;
+use crate::locator::Locator;
+use crate::object::Ob;
+use itertools::Itertools;
+use log::trace;
+
+macro_rules! join {
+    ($log:expr) => {
+        $log.iter().join("; ")
+    };
+}
+
+/// The object found by a locator, the \psi suggested for its basket,
+/// and the attribute of a basket, where the search went through last.
+pub type Found = (Ob, Bk, Option<(Bk, Loc)>);
+
+/// The algorithm of finding objects by their locators, which `Emu`
+/// uses when it makes new baskets.
+pub trait Resolver {
+    fn search(&self, emu: &Emu, bk: Bk, locator: &Locator) -> Result<Found, String>;
+}
+
+/// The resolver that `Emu` uses by default.
+pub struct Standard;
+
+impl Resolver for Standard {
+    /// Suppose, the incoming locator is `^.0.@.2`. We have to find the right
+    /// object in the catalog of them and return the position of the found one
+    /// together with the suggested \psi.
+    fn search(&self, emu: &Emu, bk: Bk, locator: &Locator) -> Result<Found, String> {
+        let mut bsk = emu.basket(bk);
+        let mut attr = None;
+        let mut locs = locator.to_vec();
+        let mut ret = Err("Nothing found".to_string());
+        let mut ob = 0;
+        let mut log = vec![];
+        let mut psi: Bk = bsk.psi;
+        ret = loop {
+            if locs.is_empty() {
+                break ret;
+            }
+            let loc = locs.remove(0);
+            log.push(loc.to_string());
+            let next = match loc {
+                Loc::Root => ROOT_OB,
+                Loc::Pi => {
+                    if bsk.psi == ROOT_BK {
+                        return Err(format!("Object Φ doesn't have 𝜋: {}", join!(log)));
+                    }
+                    psi = bsk.psi;
+                    attr = Some((psi, Loc::Root));
+                    bsk = emu.basket(psi);
+                    log.push(format!("𝜋=β{}/ν{}", psi, bsk.ob));
+                    bsk.ob
+                }
+                Loc::Obj(i) => i as Ob,
+                _ => match emu.object(ob).attrs.get(&loc) {
+                    None => match emu.object(ob).attrs.get(&Loc::Phi) {
+                        None if emu.object(ob).delta.is_some() => {
+                            if !matches!(loc, Loc::Delta | Loc::Phi) {
+                                return Err(format!(
+                                    "Data object ν{} has no {}, only Δ or 𝜑 may follow it: {}",
+                                    ob,
+                                    loc,
+                                    join!(log)
+                                ));
+                            }
+                            log.push(format!("Δ=ν{}", ob));
+                            ob
+                        }
+                        None => {
+                            return Err(format!(
+                                "Can't find {} in ν{} and there is no 𝜑: {}",
+                                loc,
+                                ob,
+                                join!(log)
+                            ))
+                        }
+                        Some((p, _psi)) => {
+                            locs.insert(0, loc.clone());
+                            attr = Some((attr.unwrap().0, loc));
+                            locs.splice(0..0, p.to_vec());
+                            log.push(format!("++{}", p));
+                            ob
+                        }
+                    },
+                    Some((p, _psi)) => {
+                        attr = Some((attr.unwrap().0, loc.clone()));
+                        locs.splice(0..0, p.to_vec());
+                        log.push(format!("+{}", p));
+                        ob
+                    }
+                },
+            };
+            ob = next;
+            ret = Ok((next, psi, attr.clone()))
+        };
+        if let Ok((next, _psi, _attr)) = ret.clone() {
+            if emu.object(next).is_empty() {
+                return Err(format!(
+                    "Object ν{} is found by β{}.{}, but it's empty",
+                    next, bk, locator
+                ));
+            }
+        }
+        trace!(
+            "find(β{}/ν{}, {}) -> (ν{}, β{}) : {} {}",
+            bk,
+            emu.basket(bk).ob,
+            locator,
+            ret.clone().unwrap().0,
+            ret.clone().unwrap().1,
+            join!(log),
+            if let Some((bk, loc)) = ret.clone().unwrap().2 {
+                format!("[β{}.{}]", bk, loc)
+            } else {
+                "".to_string()
+            }
+        );
+        ret
+    }
+}
+
+#[cfg(test)]
+use std::cell::Cell;
+
+#[cfg(test)]
+use std::rc::Rc;
+
+#[cfg(test)]
+use std::str::FromStr;
+
+#[cfg(test)]
+struct Counting(Rc<Cell<usize>>);
+
+#[cfg(test)]
+impl Resolver for Counting {
+    fn search(&self, emu: &Emu, bk: Bk, locator: &Locator) -> Result<Found, String> {
+        self.0.set(self.0.get() + 1);
+        Standard.search(emu, bk, locator)
+    }
+}
+
+#[test]
+fn uses_custom_resolver() {
+    let mut emu = Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ λ ↦ int-add, ρ ↦ 𝜋.𝛼0, 𝛼0 ↦ 𝜋.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋), 𝛼1 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap();
+    let calls = Rc::new(Cell::new(0));
+    emu.set_resolver(Counting(calls.clone()));
+    assert_eq!(84, emu.dataize().0);
+    assert_eq!(4, calls.get());
+}
+
+#[cfg(test)]
+use crate::assert_dataized_eq;
+
+#[cfg(test)]
+use crate::emu::Opt;
+
+#[test]
+fn resolves_through_data_object() {
+    assert_dataized_eq!(
+        42,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0.Δ ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+        "
+    );
+    assert_dataized_eq!(
+        42,
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0.𝜑 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+        "
+    );
+}
+
+#[test]
+#[should_panic(expected = "Data object ν1 has no 𝛼1")]
+fn fails_on_missing_attribute_of_data_object() {
+    Emu::from_str(
+        "
+        ν0(𝜋) ↦ ⟦ 𝜑 ↦ ν3(𝜋) ⟧
+        ν1(𝜋) ↦ ⟦ Δ ↦ 0x002A ⟧
+        ν2(𝜋) ↦ ⟦ 𝜑 ↦ 𝜋.𝛼0.𝛼1 ⟧
+        ν3(𝜋) ↦ ⟦ 𝜑 ↦ ν2(ξ), 𝛼0 ↦ ν1(𝜋) ⟧
+        ",
+    )
+    .unwrap()
+    .dataize();
+}
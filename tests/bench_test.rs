@@ -0,0 +1,46 @@
+// Copyright (c) 2022 Yegor Bugayenko
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included
+// in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NON-INFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use assert_cmd::Command;
+
+use rstest::rstest;
+
+#[rstest]
+#[case("written_sum_test", "default")]
+#[case("written_sum_test", "DontDelete")]
+#[case("written_sum_test", "Memo+Prio")]
+#[case("written_fibonacci_test", "default")]
+#[case("written_fibonacci_test", "Memoize")]
+#[case("written_fibonacci_test", "Prioritize")]
+#[case("written_fibonacci_test", "Memo+Prio")]
+fn benches_file(#[case] file: &str, #[case] config: &str) {
+    let mut cmd = Command::cargo_bin("bench").unwrap();
+    let output = cmd
+        .arg(format!("tests/resources/{}", file))
+        .arg("1")
+        .arg(config)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("cycles"), "{}", stdout);
+    assert!(stdout.contains(&format!("\n{} ", config)), "{}", stdout);
+}
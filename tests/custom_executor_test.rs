@@ -28,3 +28,130 @@ fn call_custom_executor() {
         .success()
         .stdout("Executor result: 84\n");
 }
+
+#[test]
+fn runs_large_program_with_more_objects() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    cmd.arg("--max-objects")
+        .arg("32")
+        .arg("tests/resources/written_large_test")
+        .assert()
+        .success()
+        .stdout("Executor result: 42\n");
+}
+
+#[test]
+fn fails_on_large_program_with_default_capacity() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    let output = cmd
+        .arg("tests/resources/written_large_test")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Object ν16 is out of capacity, only 16 objects are allowed"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn reports_unreachable_objects_with_lint() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    cmd.arg("--lint")
+        .arg("tests/resources/written_sum_test")
+        .assert()
+        .success()
+        .stdout("Executor result: 84\n")
+        .stderr("Object ν5 is unreachable from ν0\n");
+}
+
+#[test]
+fn reports_all_errors_with_check() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    let output = cmd
+        .arg("--check")
+        .arg("tests/resources/written_broken_test")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("written_broken_test:2: "), "{}", stderr);
+    assert!(
+        stderr.contains("written_broken_test:3: Unknown lambda"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("There are 2 errors"), "{}", stderr);
+}
+
+#[test]
+fn prints_binary_result() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    cmd.arg("--binary")
+        .arg("tests/resources/written_test_example")
+        .assert()
+        .success()
+        .stdout(vec![0x00, 0x54]);
+}
+
+#[test]
+fn traces_with_auto_advance() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    let output = cmd
+        .arg("--trace-auto")
+        .arg("1")
+        .arg("tests/resources/written_sum_test")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Cycle #1:\n"), "{}", stdout);
+    assert!(stdout.ends_with("Executor result: 84\n"), "{}", stdout);
+}
+
+#[test]
+fn passes_within_perf_bounds() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    cmd.arg("--max-cycles")
+        .arg("10000")
+        .arg("--max-atoms")
+        .arg("10000")
+        .arg("tests/resources/written_fibonacci_test")
+        .assert()
+        .success()
+        .stdout("Executor result: 21\n");
+}
+
+#[test]
+fn fails_out_of_perf_bounds() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    let output = cmd
+        .arg("--max-atoms")
+        .arg("5")
+        .arg("tests/resources/written_fibonacci_test")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("atom calls, while no more than 5 are allowed"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn prints_object_graph() {
+    let mut cmd = Command::cargo_bin("custom_executor").unwrap();
+    cmd.arg("--graph")
+        .arg("tests/resources/written_sum_test")
+        .assert()
+        .success()
+        .stdout("ν0.𝜑 → ν3\nν3.𝛼0 → ν1\nν3.𝛼1 → ν1\nν3.𝜑 → ν2\nν5.𝜑 → ν3\n");
+}